@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+
+use crate::{
+    coverage::{self, DatasetConformance},
+    semconv::{Attribute, TypeMismatch},
+    AppState, FilterParams, SearchParams,
+};
+
+/// Routes for the versioned JSON API, nested under `/api/v1`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/attributes", get(attributes_handler))
+        .route("/attributes/:key", get(attribute_handler))
+        .route("/search", get(search_handler))
+        .route("/tree/:path", get(tree_handler))
+        .route("/groups", get(groups_handler))
+        .route("/groups/:id", get(group_handler))
+        .route("/registries", get(registries_handler))
+        .route("/registries/:name", get(registry_handler))
+        .route("/datasets", get(datasets_handler))
+        .route("/datasets/:slug", get(dataset_handler))
+        .route("/undefined", get(undefined_handler))
+        .route("/mismatches", get(mismatches_handler))
+        .route("/usedby/:key", get(used_by_handler))
+        .route("/problems", get(problems_handler))
+        .route("/diff", get(diff_handler))
+}
+
+#[derive(Serialize)]
+struct DatasetSummary {
+    dataset: String,
+    columns: usize,
+    conformance: usize,
+}
+
+#[derive(Serialize)]
+struct DatasetDetail {
+    conformance: usize,
+    #[serde(flatten)]
+    columns: DatasetConformance,
+}
+
+#[derive(Serialize)]
+struct UsedBy<'a> {
+    datasets: &'a [String],
+    /// The datasets using each key of a template attribute.
+    suffixes: Option<&'a BTreeMap<String, Vec<String>>>,
+}
+
+async fn attributes_handler(State(state): State<AppState>) -> Response {
+    let model = state.model();
+    let attributes: BTreeMap<&String, &Attribute> = model.attribute_map.iter().collect();
+    Json(attributes).into_response()
+}
+
+async fn attribute_handler(State(state): State<AppState>, Path(key): Path<String>) -> Response {
+    let model = state.model();
+    match model.attribute_map.get(&key) {
+        Some(attribute) => Json(attribute).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn search_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    let model = state.model();
+    Json(model.search.search(&params.q.unwrap_or_default())).into_response()
+}
+
+async fn tree_handler(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Query(filter): Query<FilterParams>,
+) -> Response {
+    let model = state.model();
+    let db = filter.apply(&model.db);
+    if path == "root" {
+        return Json(db.as_ref()).into_response();
+    }
+    match db.get_node(&path) {
+        Some(node) => Json(node).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn groups_handler(State(state): State<AppState>) -> Response {
+    let model = state.model();
+    Json(&model.groups).into_response()
+}
+
+async fn group_handler(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let model = state.model();
+    match model.groups.get(&id) {
+        Some(group) => Json(group).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn registries_handler(State(state): State<AppState>) -> Response {
+    Json(&state.model().registries).into_response()
+}
+
+async fn registry_handler(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let model = state.model();
+    match model.registries.get(&name) {
+        Some(registry) => Json(registry).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn datasets_handler(State(state): State<AppState>) -> Response {
+    let model = state.model();
+    let snapshot = state.snapshot();
+    let datasets: Vec<DatasetSummary> = snapshot
+        .datasets
+        .keys()
+        .filter_map(|dataset| {
+            coverage::dataset_conformance(dataset, &snapshot, &model.attribute_map)
+        })
+        .map(|dataset| DatasetSummary {
+            columns: dataset.column_count(),
+            conformance: dataset.conformance(),
+            dataset: dataset.dataset,
+        })
+        .collect();
+    Json(datasets).into_response()
+}
+
+async fn dataset_handler(State(state): State<AppState>, Path(slug): Path<String>) -> Response {
+    let model = state.model();
+    match coverage::dataset_conformance(&slug, &state.snapshot(), &model.attribute_map) {
+        Some(columns) => Json(DatasetDetail {
+            conformance: columns.conformance(),
+            columns,
+        })
+        .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn undefined_handler(State(state): State<AppState>) -> Response {
+    let model = state.model();
+    Json(coverage::undefined_columns(
+        &state.snapshot(),
+        &model.attribute_map,
+    ))
+    .into_response()
+}
+
+async fn mismatches_handler(State(state): State<AppState>) -> Response {
+    let model = state.model();
+    let mismatches: BTreeMap<&String, &Vec<TypeMismatch>> = model
+        .attribute_map
+        .iter()
+        .filter_map(|(key, attribute)| Some((key, attribute.type_mismatches.as_ref()?)))
+        .collect();
+    Json(mismatches).into_response()
+}
+
+async fn used_by_handler(State(state): State<AppState>, Path(key): Path<String>) -> Response {
+    let model = state.model();
+    match model.attribute_map.get(&key) {
+        Some(attribute) => Json(UsedBy {
+            datasets: attribute.used_by.as_deref().unwrap_or_default(),
+            suffixes: attribute.template_suffixes.as_ref(),
+        })
+        .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn problems_handler(State(state): State<AppState>) -> Response {
+    Json(&state.model().problems).into_response()
+}
+
+async fn diff_handler(State(state): State<AppState>) -> Response {
+    match state.diff() {
+        Some(diff) => Json(diff).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
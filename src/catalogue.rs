@@ -0,0 +1,138 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::semconv::{attributes_with_prefix, Attribute};
+
+/// An attribute as a row of the catalogue tables.
+#[derive(Debug, Serialize)]
+pub struct Row {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    pub registry: String,
+    pub defined_in: String,
+    pub deprecated: String,
+    pub brief: String,
+    pub examples: String,
+    pub used_by: String,
+}
+
+/// The attributes whose keys start with `prefix`, e.g. `http.`, sorted by key.
+pub fn rows(attribute_map: &HashMap<String, Attribute>, prefix: Option<&str>) -> Vec<Row> {
+    attributes_with_prefix(attribute_map, prefix)
+        .into_iter()
+        .map(|(key, attribute)| {
+            // a template attribute is used by the datasets using any of its keys
+            let mut used_by: BTreeSet<&String> = attribute.used_by.iter().flatten().collect();
+            for datasets in attribute.template_suffixes.iter().flat_map(|s| s.values()) {
+                used_by.extend(datasets);
+            }
+            Row {
+                key: key.to_owned(),
+                r#type: attribute
+                    .r#type
+                    .as_ref()
+                    .map(|t| t.to_string())
+                    .unwrap_or_default(),
+                registry: attribute.registry_name.clone().unwrap_or_default(),
+                defined_in: attribute.defined_in.clone().unwrap_or_default(),
+                deprecated: attribute
+                    .deprecated
+                    .as_ref()
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                brief: attribute
+                    .brief
+                    .clone()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned(),
+                examples: attribute
+                    .examples
+                    .as_ref()
+                    .map(|e| e.to_string())
+                    .unwrap_or_default(),
+                used_by: used_by.into_iter().cloned().collect::<Vec<_>>().join(", "),
+            }
+        })
+        .collect()
+}
+
+pub fn to_csv(rows: &[Row]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+pub fn to_markdown(rows: &[Row]) -> String {
+    // keep each value in its cell
+    let cell = |value: &str| value.replace('|', "\\|").replace('\n', " ");
+    let mut markdown = String::from(
+        "| key | type | registry | defined in | deprecated | brief | examples | used by |\n\
+         | --- | --- | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for row in rows {
+        markdown.push_str(&format!(
+            "| `{}` | {} | {} | {} | {} | {} | {} | {} |\n",
+            row.key,
+            cell(&row.r#type),
+            cell(&row.registry),
+            cell(&row.defined_in),
+            cell(&row.deprecated),
+            cell(&row.brief),
+            cell(&row.examples),
+            cell(&row.used_by),
+        ));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semconv::attribute_map;
+
+    #[test]
+    fn test_catalogue() {
+        let mut attribute_map = attribute_map(&[
+            (
+                "http.request.method",
+                "id: method\ntype: string\nbrief: \"The method, e.g. GET | POST.\"\n\
+                 examples: [\"GET\", \"POST\"]\nused_by: [frontend, backend]",
+            ),
+            (
+                "http.flavor",
+                "id: flavor\ntype: string\nbrief: Old.\ndeprecated:\n  reason: obsoleted",
+            ),
+            (
+                "db.system",
+                "id: system\ntype: string\nbrief: The database.",
+            ),
+        ]);
+        for attribute in attribute_map.values_mut() {
+            attribute.registry_name = Some("🔭".to_owned());
+        }
+
+        let rows = rows(&attribute_map, Some("http."));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, "http.flavor");
+        assert_eq!(rows[0].deprecated, "Deprecated: obsoleted");
+
+        let csv = to_csv(&rows).unwrap();
+        assert_eq!(
+            csv.lines().next(),
+            Some("key,type,registry,defined_in,deprecated,brief,examples,used_by")
+        );
+        assert!(csv.contains(
+            "http.request.method,string,🔭,,,\"The method, e.g. GET | POST.\",\"GET, POST\",\"backend, frontend\""
+        ));
+
+        let markdown = to_markdown(&rows);
+        assert!(markdown.contains(
+            "| `http.request.method` | string | 🔭 |  |  | The method, e.g. GET \\| POST. | GET, POST | backend, frontend |"
+        ));
+    }
+}
@@ -0,0 +1,499 @@
+use std::collections::HashMap;
+
+use crate::semconv::{Attribute, ComplexType, Deprecated, Member, MemberValue, Type};
+
+/// Split a key or member id into lowercase words, e.g. `http.request.method`
+/// into `http`, `request` and `method`.
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// `HTTP_REQUEST_METHOD`
+fn screaming_case(name: &str) -> String {
+    let name = words(name).join("_").to_uppercase();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// `HttpRequestMethod`
+fn camel_case(name: &str) -> String {
+    let name: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{name}")
+    } else {
+        name
+    }
+}
+
+/// A double quoted string literal, valid in every generated language.
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+fn member_name(member: &Member) -> String {
+    member
+        .id
+        .clone()
+        .unwrap_or_else(|| member.value.to_string())
+}
+
+fn member_literal(value: &MemberValue) -> String {
+    match value {
+        MemberValue::StringType(s) => quote(s),
+        MemberValue::IntegerType(i) => i.to_string(),
+    }
+}
+
+fn is_int_enum(complex: &ComplexType) -> bool {
+    complex
+        .members
+        .iter()
+        .all(|member| matches!(member.value, MemberValue::IntegerType(_)))
+}
+
+fn deprecation(deprecated: &Deprecated) -> String {
+    let reason = match deprecated {
+        Deprecated::Renamed { renamed_to, .. } => format!("Renamed to `{renamed_to}`."),
+        Deprecated::Obsoleted { .. } => "Obsoleted.".to_owned(),
+        Deprecated::Uncategorized { .. } => "Deprecated.".to_owned(),
+        Deprecated::Text(text) => text.trim().to_owned(),
+    };
+    match deprecated.note() {
+        Some(note) => format!("{reason} {}", note.trim()),
+        None => reason,
+    }
+}
+
+/// The documentation of an attribute: its brief and note, and how to use a
+/// template. Deprecations are added in each language's own form.
+fn doc_lines(attribute: &Attribute) -> Vec<String> {
+    let mut paragraphs = vec![];
+    for text in [&attribute.brief, &attribute.note].into_iter().flatten() {
+        paragraphs.push(text.trim().to_owned());
+    }
+    if attribute.is_template_type() {
+        paragraphs.push("This is a template: append `.<key>` to the name.".to_owned());
+    }
+    let mut lines = vec![];
+    for paragraph in paragraphs.iter().filter(|p| !p.is_empty()) {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(paragraph.lines().map(|line| line.trim_end().to_owned()));
+    }
+    lines
+}
+
+fn comment(prefix: &str, lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("{prefix}{line}").trim_end().to_owned() + "\n")
+        .collect()
+}
+
+fn complex_type(attribute: &Attribute) -> Option<&ComplexType> {
+    match &attribute.r#type {
+        Some(Type::Complex(complex)) => Some(complex),
+        _ => None,
+    }
+}
+
+/// Fail if two attributes, or two members of one enum, would generate the
+/// same identifier, e.g. `a.b_c` and `a.b.c`. `names` gives an attribute's
+/// top level identifiers and `member` a member's identifier in its enum.
+fn check_identifiers(
+    attributes: &[(&str, &Attribute)],
+    names: impl Fn(&str, &Attribute) -> Vec<String>,
+    member: fn(&str) -> String,
+) -> anyhow::Result<()> {
+    fn unique(names: impl IntoIterator<Item = (String, String)>) -> anyhow::Result<()> {
+        let mut seen: HashMap<String, String> = HashMap::new();
+        for (identifier, source) in names {
+            match seen.get(&identifier) {
+                Some(other) if *other != source => anyhow::bail!(
+                    "{other} and {source} would both generate the identifier {identifier}"
+                ),
+                Some(_) => {}
+                None => {
+                    seen.insert(identifier, source);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    unique(attributes.iter().flat_map(|(key, attribute)| {
+        names(key, attribute)
+            .into_iter()
+            .map(move |name| (name, format!("`{key}`")))
+    }))?;
+    for (key, attribute) in attributes {
+        if let Some(complex) = complex_type(attribute) {
+            unique(complex.members.iter().map(|m| {
+                let name = member_name(m);
+                (member(&name), format!("member `{name}` of `{key}`"))
+            }))?;
+        }
+    }
+    Ok(())
+}
+
+/// The constant and, for enums, the values type of an attribute.
+fn constant_and_values(constant: fn(&str) -> String) -> impl Fn(&str, &Attribute) -> Vec<String> {
+    move |key, attribute| {
+        let mut names = vec![constant(key)];
+        if complex_type(attribute).is_some() {
+            names.push(format!("{}Values", camel_case(key)));
+        }
+        names
+    }
+}
+
+pub fn rust(attributes: &[(&str, &Attribute)]) -> anyhow::Result<String> {
+    check_identifiers(attributes, constant_and_values(screaming_case), camel_case)?;
+    let mut code = String::from("//! Semantic convention attributes generated by honey-explore.\n");
+    for (key, attribute) in attributes {
+        let name = screaming_case(key);
+        code.push('\n');
+        code.push_str(&comment("/// ", &doc_lines(attribute)));
+        if let Some(deprecated) = &attribute.deprecated {
+            code.push_str(&format!(
+                "#[deprecated(note = {})]\n",
+                quote(&deprecation(deprecated))
+            ));
+        }
+        code.push_str(&format!("pub const {name}: &str = {};\n", quote(key)));
+
+        let Some(complex) = complex_type(attribute) else {
+            continue;
+        };
+        let enum_name = format!("{}Values", camel_case(key));
+        let value_type = if is_int_enum(complex) {
+            "i64"
+        } else {
+            "&'static str"
+        };
+        code.push_str(&format!("\n/// Values of [`{name}`].\n"));
+        code.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq)]\n");
+        code.push_str(&format!("pub enum {enum_name} {{\n"));
+        for member in &complex.members {
+            code.push_str(&comment("    /// ", &member_brief(member)));
+            code.push_str(&format!("    {},\n", camel_case(&member_name(member))));
+        }
+        code.push_str("}\n\n");
+        code.push_str(&format!("impl {enum_name} {{\n"));
+        code.push_str(&format!(
+            "    pub const fn value(&self) -> {value_type} {{\n"
+        ));
+        code.push_str("        match self {\n");
+        for member in &complex.members {
+            let value = match (&member.value, value_type) {
+                (MemberValue::IntegerType(i), "&'static str") => quote(&i.to_string()),
+                (value, _) => member_literal(value),
+            };
+            code.push_str(&format!(
+                "            Self::{} => {value},\n",
+                camel_case(&member_name(member))
+            ));
+        }
+        code.push_str("        }\n    }\n}\n");
+    }
+    Ok(code)
+}
+
+pub fn go(attributes: &[(&str, &Attribute)]) -> anyhow::Result<String> {
+    // the members are constants in the package too
+    check_identifiers(
+        attributes,
+        |key, attribute| {
+            let mut names = constant_and_values(camel_case)(key, attribute);
+            for member in complex_type(attribute).iter().flat_map(|c| &c.members) {
+                names.push(format!(
+                    "{}{}",
+                    camel_case(key),
+                    camel_case(&member_name(member))
+                ));
+            }
+            names
+        },
+        camel_case,
+    )?;
+    let mut code =
+        String::from("// Code generated by honey-explore. DO NOT EDIT.\n\npackage semconv\n");
+    for (key, attribute) in attributes {
+        let name = camel_case(key);
+        let mut lines = vec![format!("{name} is the {} attribute.", quote(key))];
+        let doc = doc_lines(attribute);
+        if !doc.is_empty() {
+            lines.push(String::new());
+            lines.extend(doc);
+        }
+        if let Some(deprecated) = &attribute.deprecated {
+            lines.push(String::new());
+            lines.push(format!("Deprecated: {}", deprecation(deprecated)));
+        }
+        code.push('\n');
+        code.push_str(&comment("// ", &lines));
+        code.push_str(&format!("const {name} = {}\n", quote(key)));
+
+        let Some(complex) = complex_type(attribute) else {
+            continue;
+        };
+        let type_name = format!("{name}Values");
+        let underlying = if is_int_enum(complex) {
+            "int64"
+        } else {
+            "string"
+        };
+        code.push_str(&format!(
+            "\n// {type_name} are the values of {name}.\ntype {type_name} {underlying}\n\nconst (\n"
+        ));
+        for member in &complex.members {
+            code.push_str(&comment("\t// ", &member_brief(member)));
+            let value = match (&member.value, underlying) {
+                (MemberValue::IntegerType(i), "string") => quote(&i.to_string()),
+                (value, _) => member_literal(value),
+            };
+            code.push_str(&format!(
+                "\t{name}{} {type_name} = {value}\n",
+                camel_case(&member_name(member))
+            ));
+        }
+        code.push_str(")\n");
+    }
+    Ok(code)
+}
+
+/// A Python docstring, escaped so it can't end early.
+fn docstring(indent: &str, lines: &[String]) -> String {
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| line.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\""))
+        .collect();
+    match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("{indent}\"\"\"{line}\"\"\"\n"),
+        [first, rest @ ..] => {
+            let mut docstring = format!("{indent}\"\"\"{first}\n");
+            docstring.push_str(&comment(indent, rest));
+            docstring.push_str(&format!("{indent}\"\"\"\n"));
+            docstring
+        }
+    }
+}
+
+pub fn python(attributes: &[(&str, &Attribute)]) -> anyhow::Result<String> {
+    check_identifiers(
+        attributes,
+        constant_and_values(screaming_case),
+        screaming_case,
+    )?;
+    let mut code =
+        String::from("\"\"\"Semantic convention attributes generated by honey-explore.\"\"\"\n\n");
+    if attributes
+        .iter()
+        .any(|(_, attribute)| complex_type(attribute).is_some())
+    {
+        code.push_str("from enum import Enum\n");
+    }
+    code.push_str("from typing import Final\n");
+    for (key, attribute) in attributes {
+        let name = screaming_case(key);
+        let mut lines = doc_lines(attribute);
+        if let Some(deprecated) = &attribute.deprecated {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("Deprecated: {}", deprecation(deprecated)));
+        }
+        code.push_str(&format!("\n{name}: Final = {}\n", quote(key)));
+        code.push_str(&docstring("", &lines));
+
+        let Some(complex) = complex_type(attribute) else {
+            continue;
+        };
+        code.push_str(&format!(
+            "\n\nclass {}Values(Enum):\n    \"\"\"Values of `{name}`.\"\"\"\n",
+            camel_case(key)
+        ));
+        for member in &complex.members {
+            code.push_str(&format!(
+                "\n    {} = {}\n",
+                screaming_case(&member_name(member)),
+                member_literal(&member.value)
+            ));
+            code.push_str(&docstring("    ", &member_brief(member)));
+        }
+        code.push('\n');
+    }
+    Ok(code)
+}
+
+pub fn typescript(attributes: &[(&str, &Attribute)]) -> anyhow::Result<String> {
+    check_identifiers(
+        attributes,
+        constant_and_values(screaming_case),
+        screaming_case,
+    )?;
+    let jsdoc = |indent: &str, lines: &[String]| -> String {
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| line.replace("*/", "*\\/"))
+            .collect();
+        match lines.as_slice() {
+            [] => String::new(),
+            [line] => format!("{indent}/** {line} */\n"),
+            lines => format!(
+                "{indent}/**\n{}{indent} */\n",
+                comment(&format!("{indent} * "), lines)
+            ),
+        }
+    };
+    let mut code = String::from("// Semantic convention attributes generated by honey-explore.\n");
+    for (key, attribute) in attributes {
+        let name = screaming_case(key);
+        let mut lines = doc_lines(attribute);
+        if let Some(deprecated) = &attribute.deprecated {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("@deprecated {}", deprecation(deprecated)));
+        }
+        code.push('\n');
+        code.push_str(&jsdoc("", &lines));
+        code.push_str(&format!("export const {name} = {};\n", quote(key)));
+
+        let Some(complex) = complex_type(attribute) else {
+            continue;
+        };
+        let values = format!("{}Values", camel_case(key));
+        code.push_str(&format!(
+            "\n/** Values of {{@link {name}}}. */\nexport const {values} = {{\n"
+        ));
+        for member in &complex.members {
+            code.push_str(&jsdoc("  ", &member_brief(member)));
+            code.push_str(&format!(
+                "  {}: {},\n",
+                screaming_case(&member_name(member)),
+                member_literal(&member.value)
+            ));
+        }
+        code.push_str(&format!(
+            "}} as const;\n\nexport type {values} = (typeof {values})[keyof typeof {values}];\n"
+        ));
+    }
+    Ok(code)
+}
+
+fn member_brief(member: &Member) -> Vec<String> {
+    member
+        .brief
+        .iter()
+        .flat_map(|brief| brief.trim().lines())
+        .map(|line| line.trim_end().to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semconv::{attribute_map, attributes_with_prefix};
+
+    #[test]
+    fn test_names() {
+        assert_eq!(screaming_case("http.request.method"), "HTTP_REQUEST_METHOD");
+        assert_eq!(camel_case("http.request.method"), "HttpRequestMethod");
+        assert_eq!(camel_case("1.0"), "V10");
+        assert_eq!(screaming_case("k8s.pod-name"), "K8S_POD_NAME");
+    }
+
+    #[test]
+    fn test_codegen() {
+        let attribute_map = attribute_map(&[
+            (
+                "http.request.method",
+                "id: method\n\
+                 type:\n  allow_custom_values: true\n  members:\n\
+                 \x20   - id: get\n      value: GET\n      brief: GET method.\n\
+                 \x20   - id: post\n      value: POST\n\
+                 brief: HTTP request method.",
+            ),
+            (
+                "http.method",
+                "id: method\ntype: string\nbrief: Old method.\n\
+                 deprecated:\n  reason: renamed\n  renamed_to: http.request.method",
+            ),
+            ("db.system", "id: system\ntype: string"),
+        ]);
+        let attributes = attributes_with_prefix(&attribute_map, Some("http."));
+        assert_eq!(attributes.len(), 2);
+
+        let rust = rust(&attributes).unwrap();
+        assert!(rust.contains(
+            "/// Old method.\n\
+             #[deprecated(note = \"Renamed to `http.request.method`.\")]\n\
+             pub const HTTP_METHOD: &str = \"http.method\";\n"
+        ));
+        assert!(rust.contains(
+            "pub enum HttpRequestMethodValues {\n    /// GET method.\n    Get,\n    Post,\n}"
+        ));
+        assert!(rust.contains("            Self::Post => \"POST\",\n"));
+
+        let go = go(&attributes).unwrap();
+        assert!(go.contains("// Deprecated: Renamed to `http.request.method`.\nconst HttpMethod = \"http.method\"\n"));
+        assert!(go.contains("\tHttpRequestMethodGet HttpRequestMethodValues = \"GET\"\n"));
+
+        let python = python(&attributes).unwrap();
+        assert!(python.contains("HTTP_REQUEST_METHOD: Final = \"http.request.method\"\n\"\"\"HTTP request method.\"\"\"\n"));
+        assert!(python.contains("    GET = \"GET\"\n    \"\"\"GET method.\"\"\"\n"));
+
+        let typescript = typescript(&attributes).unwrap();
+        assert!(typescript.contains("/**\n * Old method.\n *\n * @deprecated Renamed to `http.request.method`.\n */\nexport const HTTP_METHOD = \"http.method\";\n"));
+        assert!(typescript.contains("  /** GET method. */\n  GET: \"GET\",\n"));
+    }
+
+    #[test]
+    fn test_identifier_collisions() {
+        let attribute_map = attribute_map(&[
+            ("a.b_c", "id: b_c\ntype: string"),
+            ("a.b.c", "id: c\ntype: string"),
+            (
+                "d",
+                "id: d\ntype:\n  members:\n    - id: x.y\n      value: x\n    - id: x_y\n      value: y",
+            ),
+        ]);
+
+        let attributes = attributes_with_prefix(&attribute_map, Some("a."));
+        assert_eq!(
+            rust(&attributes).unwrap_err().to_string(),
+            "`a.b.c` and `a.b_c` would both generate the identifier A_B_C"
+        );
+        let attributes = attributes_with_prefix(&attribute_map, Some("d"));
+        assert_eq!(
+            python(&attributes).unwrap_err().to_string(),
+            "member `x.y` of `d` and member `x_y` of `d` would both generate the identifier X_Y"
+        );
+        assert!(go(&attributes).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{Display, Formatter},
+};
+
+use serde::Serialize;
+
+use crate::{
+    semconv::{Attribute, ComplexType, MemberValue, PrimitiveType, Type},
+    snapshot::Snapshot,
+};
+
+/// A Honeycomb column that matches no attribute or template prefix.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UndefinedColumn {
+    pub key_name: String,
+    pub datasets: Vec<String>,
+}
+
+/// A dataset's columns split by how they match the conventions.
+#[derive(Debug, Default, Serialize)]
+pub struct DatasetConformance {
+    pub dataset: String,
+    /// Pairs of column and the attribute it matches.
+    pub defined: Vec<(String, String)>,
+    pub deprecated: Vec<(String, String)>,
+    pub unknown: Vec<String>,
+    /// Conventions in the namespaces the dataset uses that it doesn't emit.
+    pub unused: BTreeMap<String, Vec<String>>,
+}
+
+impl DatasetConformance {
+    pub fn column_count(&self) -> usize {
+        self.defined.len() + self.deprecated.len() + self.unknown.len()
+    }
+
+    /// The percentage of columns matching a current convention.
+    pub fn conformance(&self) -> usize {
+        match self.column_count() {
+            0 => 100,
+            count => (self.defined.len() * 100 + count / 2) / count,
+        }
+    }
+}
+
+/// How an enum value compares with the values observed in Honeycomb.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueStatus {
+    Defined,
+    Undefined,
+    NeverObserved,
+}
+
+impl Display for ValueStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueStatus::Defined => write!(f, "defined"),
+            ValueStatus::Undefined => write!(f, "undefined"),
+            ValueStatus::NeverObserved => write!(f, "never observed"),
+        }
+    }
+}
+
+/// An enum member or observed value and the datasets it was seen in, with
+/// the number of events in each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumValue {
+    pub value: String,
+    pub status: ValueStatus,
+    pub datasets: Vec<(String, u64)>,
+}
+
+impl EnumValue {
+    /// The number of events with this value across every dataset.
+    pub fn count(&self) -> u64 {
+        self.datasets.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Compare the values and event counts observed per dataset against the
+/// members of an enum. Members come first in their declared order, followed
+/// by the undefined values seen in the most datasets, then the most events.
+pub fn enum_conformance(
+    complex: &ComplexType,
+    observed: &BTreeMap<String, Vec<(String, u64)>>,
+) -> Vec<EnumValue> {
+    let mut seen_in: BTreeMap<&str, Vec<(String, u64)>> = BTreeMap::new();
+    for (dataset, values) in observed {
+        for (value, count) in values {
+            let datasets = seen_in.entry(value).or_default();
+            match datasets.iter_mut().find(|(d, _)| d == dataset) {
+                Some((_, total)) => *total += count,
+                None => datasets.push((dataset.clone(), *count)),
+            }
+        }
+    }
+
+    let mut values = vec![];
+    for member in &complex.members {
+        let value = member.value.to_string();
+        let datasets = seen_in.remove(value.as_str()).unwrap_or_default();
+        let status = if datasets.is_empty() {
+            ValueStatus::NeverObserved
+        } else {
+            ValueStatus::Defined
+        };
+        values.push(EnumValue {
+            value,
+            status,
+            datasets,
+        });
+    }
+    let mut undefined: Vec<EnumValue> = seen_in
+        .into_iter()
+        .map(|(value, datasets)| EnumValue {
+            value: value.to_owned(),
+            status: ValueStatus::Undefined,
+            datasets,
+        })
+        .collect();
+    undefined.sort_by_key(|value| std::cmp::Reverse((value.datasets.len(), value.count())));
+    values.extend(undefined);
+    values
+}
+
+/// Check whether a Honeycomb column type can hold the values of a
+/// convention type. Arrays are stored as JSON strings, whole number doubles
+/// may be stored as integers and unknown column types are given the benefit
+/// of the doubt.
+pub fn is_compatible(r#type: &Type, column_type: &str) -> bool {
+    if !matches!(column_type, "string" | "integer" | "float" | "boolean") {
+        return true;
+    }
+    match r#type {
+        Type::Simple(primitive) => match primitive {
+            PrimitiveType::Any => true,
+            PrimitiveType::String | PrimitiveType::TemplateOfString => column_type == "string",
+            PrimitiveType::Int | PrimitiveType::TemplateOfInt => column_type == "integer",
+            PrimitiveType::Double | PrimitiveType::TemplateOfDouble => {
+                matches!(column_type, "float" | "integer")
+            }
+            PrimitiveType::Boolean | PrimitiveType::TemplateOfBoolean => column_type == "boolean",
+            PrimitiveType::ArrayOfString
+            | PrimitiveType::ArrayOfInt
+            | PrimitiveType::ArrayOfDouble
+            | PrimitiveType::ArrayOfBoolean
+            | PrimitiveType::TemplateOfArrayOfString
+            | PrimitiveType::TemplateOfArrayOfInt
+            | PrimitiveType::TemplateOfArrayOfDouble
+            | PrimitiveType::TemplateOfArrayOfBoolean => column_type == "string",
+        },
+        Type::Complex(complex) => {
+            // open enums may also carry custom string values, whatever their
+            // members' type
+            (complex.allow_custom_values && column_type == "string")
+                || complex.members.iter().any(|member| {
+                    matches!(
+                        (&member.value, column_type),
+                        (MemberValue::StringType(_), "string")
+                            | (MemberValue::IntegerType(_), "integer")
+                    )
+                })
+        }
+    }
+}
+
+/// Find the attribute for a column, either directly or as a key of a
+/// template attribute.
+pub fn matching_attribute<'a>(
+    attribute_map: &'a HashMap<String, Attribute>,
+    key_name: &str,
+) -> Option<(&'a String, &'a Attribute)> {
+    if let Some(entry) = attribute_map.get_key_value(key_name) {
+        return Some(entry);
+    }
+    let (prefix, _) = key_name.rsplit_once('.')?;
+    attribute_map
+        .get_key_value(prefix)
+        .filter(|(_, attribute)| attribute.is_template_type())
+}
+
+/// Check whether a column is an attribute or a key of a template attribute.
+pub fn is_defined(attribute_map: &HashMap<String, Attribute>, key_name: &str) -> bool {
+    matching_attribute(attribute_map, key_name).is_some()
+}
+
+/// The top level namespace of a column, e.g. `http` for `http.request.method`.
+pub fn namespace(key_name: &str) -> &str {
+    key_name
+        .split_once('.')
+        .map(|(namespace, _)| namespace)
+        .unwrap_or(key_name)
+}
+
+/// Every column not defined by a convention, grouped by namespace.
+pub fn undefined_columns(
+    snapshot: &Snapshot,
+    attribute_map: &HashMap<String, Attribute>,
+) -> BTreeMap<String, Vec<UndefinedColumn>> {
+    let mut columns: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (dataset, dataset_columns) in &snapshot.datasets {
+        for column in dataset_columns.iter() {
+            if !is_defined(attribute_map, &column.key_name) {
+                columns
+                    .entry(&column.key_name)
+                    .or_default()
+                    .push(dataset.clone());
+            }
+        }
+    }
+
+    let mut namespaces: BTreeMap<String, Vec<UndefinedColumn>> = BTreeMap::new();
+    for (key_name, datasets) in columns {
+        namespaces
+            .entry(namespace(key_name).to_owned())
+            .or_default()
+            .push(UndefinedColumn {
+                key_name: key_name.to_owned(),
+                datasets,
+            });
+    }
+    namespaces
+}
+
+pub fn dataset_conformance(
+    dataset: &str,
+    snapshot: &Snapshot,
+    attribute_map: &HashMap<String, Attribute>,
+) -> Option<DatasetConformance> {
+    let columns = snapshot.datasets.get(dataset)?;
+    let mut conformance = DatasetConformance {
+        dataset: dataset.to_owned(),
+        ..Default::default()
+    };
+    let mut used = BTreeSet::new();
+    let mut namespaces = BTreeSet::new();
+    for column in columns.iter() {
+        namespaces.insert(namespace(&column.key_name));
+        match matching_attribute(attribute_map, &column.key_name) {
+            Some((key, attribute)) => {
+                used.insert(key);
+                let matched = (column.key_name.clone(), key.clone());
+                if attribute.deprecated.is_some() {
+                    conformance.deprecated.push(matched);
+                } else {
+                    conformance.defined.push(matched);
+                }
+            }
+            None => conformance.unknown.push(column.key_name.clone()),
+        }
+    }
+    for (key, attribute) in attribute_map {
+        if attribute.deprecated.is_none()
+            && !used.contains(key)
+            && namespaces.contains(namespace(key))
+        {
+            conformance
+                .unused
+                .entry(namespace(key).to_owned())
+                .or_default()
+                .push(key.clone());
+        }
+    }
+    conformance.defined.sort();
+    conformance.deprecated.sort();
+    conformance.unknown.sort();
+    for keys in conformance.unused.values_mut() {
+        keys.sort();
+    }
+    Some(conformance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::Column;
+    use std::sync::Arc;
+
+    fn column(key_name: &str) -> Column {
+        Column::new(key_name, "string")
+    }
+
+    fn attribute_map() -> HashMap<String, Attribute> {
+        crate::semconv::attribute_map(&[
+            ("http.request.method", "id: request.method\ntype: string"),
+            (
+                "http.request.header",
+                "id: request.header\ntype: template[string[]]",
+            ),
+            (
+                "http.method",
+                "id: method\ntype: string\ndeprecated: Use http.request.method",
+            ),
+            ("http.route", "id: route\ntype: string"),
+            ("db.system", "id: system\ntype: string"),
+        ])
+    }
+
+    #[test]
+    fn test_dataset_conformance() {
+        let attribute_map = attribute_map();
+        let mut snapshot = Snapshot::default();
+        snapshot.datasets.insert(
+            "a".to_owned(),
+            Arc::new(vec![
+                column("http.request.method"),
+                column("http.request.header.host"),
+                column("http.method"),
+                column("http.request.size"),
+            ]),
+        );
+
+        let conformance = dataset_conformance("a", &snapshot, &attribute_map).unwrap();
+        let pair = |column: &str, key: &str| (column.to_owned(), key.to_owned());
+        assert_eq!(
+            conformance.defined,
+            vec![
+                pair("http.request.header.host", "http.request.header"),
+                pair("http.request.method", "http.request.method")
+            ]
+        );
+        assert_eq!(
+            conformance.deprecated,
+            vec![pair("http.method", "http.method")]
+        );
+        assert_eq!(conformance.unknown, vec!["http.request.size"]);
+        assert_eq!(conformance.unused.len(), 1);
+        assert_eq!(conformance.unused["http"], vec!["http.route"]);
+        assert_eq!(conformance.conformance(), 50);
+        assert!(dataset_conformance("b", &snapshot, &attribute_map).is_none());
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        let r#type = |yaml: &str| serde_yaml::from_str::<Type>(yaml).unwrap();
+        assert!(is_compatible(&r#type("string"), "string"));
+        assert!(!is_compatible(&r#type("int"), "string"));
+        assert!(is_compatible(&r#type("double"), "integer"));
+        assert!(!is_compatible(&r#type("int"), "float"));
+        assert!(is_compatible(&r#type("any"), "boolean"));
+        assert!(is_compatible(&r#type("int[]"), "string"));
+        assert!(is_compatible(&r#type("template[boolean]"), "boolean"));
+        assert!(is_compatible(&r#type("string"), "unknown"));
+        let int_enum = r#type("members:\n  - value: 1\n  - value: 2");
+        assert!(is_compatible(&int_enum, "integer"));
+        assert!(!is_compatible(&int_enum, "string"));
+    }
+
+    #[test]
+    fn test_is_compatible_open_enum() {
+        let r#type = |yaml: &str| serde_yaml::from_str::<Type>(yaml).unwrap();
+        let open_int_enum =
+            r#type("allow_custom_values: true\nmembers:\n  - value: 1\n  - value: 2");
+        assert!(is_compatible(&open_int_enum, "integer"));
+        assert!(is_compatible(&open_int_enum, "string"));
+        assert!(!is_compatible(&open_int_enum, "boolean"));
+        let open_string_enum = r#type("allow_custom_values: true\nmembers:\n  - value: a");
+        assert!(is_compatible(&open_string_enum, "string"));
+        assert!(!is_compatible(&open_string_enum, "integer"));
+    }
+
+    #[test]
+    fn test_undefined_columns() {
+        let attribute_map = attribute_map();
+
+        let mut snapshot = Snapshot::default();
+        snapshot.datasets.insert(
+            "a".to_owned(),
+            Arc::new(vec![
+                column("http.request.method"),
+                column("http.request.header.host"),
+                column("http.request.size"),
+                column("duration_ms"),
+            ]),
+        );
+        snapshot
+            .datasets
+            .insert("b".to_owned(), Arc::new(vec![column("http.request.size")]));
+
+        let undefined = undefined_columns(&snapshot, &attribute_map);
+        assert_eq!(undefined.len(), 2);
+        assert_eq!(
+            undefined["http"],
+            vec![UndefinedColumn {
+                key_name: "http.request.size".to_owned(),
+                datasets: vec!["a".to_owned(), "b".to_owned()],
+            }]
+        );
+        assert_eq!(undefined["duration_ms"][0].key_name, "duration_ms");
+    }
+
+    #[test]
+    fn test_enum_conformance() {
+        let complex: ComplexType = serde_yaml::from_str(
+            r#"
+            allow_custom_values: false
+            members:
+              - value: GET
+              - value: POST
+              - value: PUT
+            "#,
+        )
+        .unwrap();
+        let mut observed = BTreeMap::new();
+        observed.insert(
+            "backend".to_owned(),
+            vec![
+                ("GET".to_owned(), 120),
+                ("get".to_owned(), 4),
+                ("PATCH".to_owned(), 2),
+                ("DELETE".to_owned(), 9),
+            ],
+        );
+        observed.insert(
+            "frontend".to_owned(),
+            vec![("GET".to_owned(), 30), ("get".to_owned(), 1)],
+        );
+
+        let values = enum_conformance(&complex, &observed);
+        let summary: Vec<(&str, ValueStatus, usize, u64)> = values
+            .iter()
+            .map(|v| (v.value.as_str(), v.status, v.datasets.len(), v.count()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("GET", ValueStatus::Defined, 2, 150),
+                ("POST", ValueStatus::NeverObserved, 0, 0),
+                ("PUT", ValueStatus::NeverObserved, 0, 0),
+                ("get", ValueStatus::Undefined, 2, 5),
+                ("DELETE", ValueStatus::Undefined, 1, 9),
+                ("PATCH", ValueStatus::Undefined, 1, 2),
+            ]
+        );
+        assert_eq!(values[0].datasets[1], ("frontend".to_owned(), 30));
+    }
+}
@@ -1,12 +1,16 @@
-use askama::Template;
+use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet};
 
-#[derive(Template, Clone)]
-#[template(path = "tree.html")]
+#[derive(Clone, Serialize)]
 pub struct Node<T> {
     pub name: String,
     pub path: String,
     pub tags: BTreeSet<String>,
+    pub counts: BTreeMap<String, usize>,
+    /// The tags counted for this node's own value, included in the counts
+    /// of every branch above it.
+    #[serde(skip)]
+    own_counts: BTreeMap<String, usize>,
     pub value: Option<T>,
     pub children: BTreeMap<String, Node<T>>,
 }
@@ -17,6 +21,8 @@ impl<T> Node<T> {
             name,
             path,
             tags: BTreeSet::new(),
+            counts: BTreeMap::new(),
+            own_counts: BTreeMap::new(),
             value,
             children: BTreeMap::new(),
         }
@@ -50,6 +56,23 @@ impl<T> Node<T> {
         current_node.value = value;
     }
 
+    /// Count a leaf tag, e.g. its stability, on every branch above the named node.
+    pub fn add_count(&mut self, name: &str, tag: &str) {
+        let mut parts = name.split('.').peekable();
+        let mut current_node = self;
+
+        while let Some(part) = parts.next() {
+            *current_node.counts.entry(tag.to_owned()).or_insert(0) += 1;
+            match current_node.children.get_mut(part) {
+                Some(child_node) => current_node = child_node,
+                None => return,
+            }
+            if parts.peek().is_none() {
+                *current_node.own_counts.entry(tag.to_owned()).or_insert(0) += 1;
+            }
+        }
+    }
+
     pub fn get_node(&self, name: &str) -> Option<&Node<T>> {
         let parts = name.split('.');
         let mut current_node = self;
@@ -76,6 +99,53 @@ impl<T> Node<T> {
     pub fn get_tags_string(&self) -> String {
         self.tags.iter().cloned().collect::<Vec<String>>().join(" ")
     }
+
+    pub fn get_counts_string(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(tag, count)| format!("{count} {tag}"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl<T: Clone> Node<T> {
+    /// A copy of this tree keeping only the values matching the predicate
+    /// and the branches leading to them. Counts only include what's kept.
+    pub fn filter<F: Fn(&T) -> bool>(&self, predicate: &F) -> Node<T> {
+        let value = self.value.clone().filter(predicate);
+        let mut node = Node {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            tags: self.tags.clone(),
+            counts: BTreeMap::new(),
+            own_counts: if value.is_some() {
+                self.own_counts.clone()
+            } else {
+                BTreeMap::new()
+            },
+            value,
+            children: BTreeMap::new(),
+        };
+        for (name, child) in &self.children {
+            let child = child.filter(predicate);
+            if child.value.is_some() || !child.children.is_empty() {
+                for (tag, count) in child.counts.iter().chain(&child.own_counts) {
+                    *node.counts.entry(tag.clone()).or_insert(0) += count;
+                }
+                node.children.insert(name.clone(), child);
+            }
+        }
+        node
+    }
+}
+
+/// The path of the node containing `name`, or "root" for top level names.
+pub fn parent_path(name: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((parent, _)) => parent.to_owned(),
+        None => "root".to_owned(),
+    }
 }
 
 #[cfg(test)]
@@ -127,4 +197,69 @@ mod tests {
         assert!(aws_s3.children.contains_key("key"));
         assert!(!aws_s3.children.contains_key("nonexistent"));
     }
+
+    #[test]
+    fn test_add_count() {
+        let mut root = Node::new("root".to_string(), "".to_owned(), None);
+
+        root.add_node("aws.s3.bucket", "e", Some(1));
+        root.add_node("aws.s3.key", "e", Some(2));
+        root.add_node("aws.region", "e", Some(3));
+        root.add_count("aws.s3.bucket", "stable");
+        root.add_count("aws.s3.key", "development");
+        root.add_count("aws.region", "stable");
+
+        let aws = root.get_node("aws").unwrap();
+        assert_eq!(aws.get_counts_string(), "1 development, 2 stable");
+        let aws_s3 = root.get_node("aws.s3").unwrap();
+        assert_eq!(aws_s3.get_counts_string(), "1 development, 1 stable");
+        assert!(root.get_node("aws.s3.bucket").unwrap().counts.is_empty());
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut root = Node::new("root".to_string(), "".to_owned(), None);
+
+        root.add_node("aws.s3.bucket", "e", Some(1));
+        root.add_node("aws.s3.key", "e", Some(2));
+        root.add_node("aws.region", "e", Some(3));
+
+        let filtered = root.filter(&|v| *v != 3);
+        assert!(filtered.get_node("aws.s3.key").is_some());
+        assert!(filtered.get_node("aws.region").is_none());
+
+        let filtered = root.filter(&|v| *v == 3);
+        assert!(filtered.get_node("aws.s3").is_none());
+        assert_eq!(filtered.get_node("aws.region").unwrap().value, Some(3));
+    }
+
+    #[test]
+    fn test_filter_counts() {
+        let mut root = Node::new("root".to_string(), "".to_owned(), None);
+
+        root.add_node("aws.s3.bucket", "e", Some(1));
+        root.add_node("aws.s3.key", "e", Some(2));
+        root.add_node("aws.region", "e", Some(3));
+        root.add_count("aws.s3.bucket", "stable");
+        root.add_count("aws.s3.key", "development");
+        root.add_count("aws.region", "stable");
+
+        let filtered = root.filter(&|v| *v != 2);
+        assert_eq!(filtered.get_counts_string(), "2 stable");
+        assert_eq!(
+            filtered.get_node("aws").unwrap().get_counts_string(),
+            "2 stable"
+        );
+        assert_eq!(
+            filtered.get_node("aws.s3").unwrap().get_counts_string(),
+            "1 stable"
+        );
+        assert!(filtered.get_node("aws.region").unwrap().counts.is_empty());
+    }
+
+    #[test]
+    fn test_parent_path() {
+        assert_eq!(parent_path("aws.s3.bucket"), "aws.s3");
+        assert_eq!(parent_path("aws"), "root");
+    }
 }
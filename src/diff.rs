@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::semconv::{Attribute, Deprecated};
+
+/// An attribute whose type differs between the two models.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// An attribute deprecated in the new model in favour of another.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rename {
+    pub key: String,
+    pub renamed_to: String,
+}
+
+/// The attribute changes between an old and a new version of a model.
+#[derive(Debug, Default, Serialize)]
+pub struct Diff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub type_changed: Vec<TypeChange>,
+    pub deprecated: Vec<String>,
+    pub renamed: Vec<Rename>,
+}
+
+impl Diff {
+    pub fn new(old: &HashMap<String, Attribute>, new: &HashMap<String, Attribute>) -> Self {
+        let type_string = |attribute: &Attribute| {
+            attribute
+                .r#type
+                .as_ref()
+                .map(|t| t.to_string())
+                .unwrap_or_default()
+        };
+        let mut diff = Diff::default();
+        for (key, attribute) in new {
+            let Some(old_attribute) = old.get(key) else {
+                diff.added.push(key.clone());
+                continue;
+            };
+            if old_attribute.r#type != attribute.r#type {
+                diff.type_changed.push(TypeChange {
+                    key: key.clone(),
+                    old: type_string(old_attribute),
+                    new: type_string(attribute),
+                });
+            }
+            if old_attribute.deprecated.is_none() {
+                match &attribute.deprecated {
+                    Some(Deprecated::Renamed { renamed_to, .. }) => diff.renamed.push(Rename {
+                        key: key.clone(),
+                        renamed_to: renamed_to.clone(),
+                    }),
+                    Some(_) => diff.deprecated.push(key.clone()),
+                    None => {}
+                }
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|key| !new.contains_key(*key))
+            .cloned()
+            .collect();
+
+        diff.added.sort();
+        diff.removed.sort();
+        diff.type_changed.sort_by(|a, b| a.key.cmp(&b.key));
+        diff.deprecated.sort();
+        diff.renamed.sort_by(|a, b| a.key.cmp(&b.key));
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.type_changed.is_empty()
+            && self.deprecated.is_empty()
+            && self.renamed.is_empty()
+    }
+
+    /// Each non-empty section's title with its lines, `code` wrapping the
+    /// attribute keys and types.
+    fn sections(&self, code: fn(&str) -> String) -> Vec<(String, Vec<String>)> {
+        let keys = |keys: &[String]| -> Vec<String> { keys.iter().map(|key| code(key)).collect() };
+        let sections = vec![
+            ("Added", keys(&self.added)),
+            ("Removed", keys(&self.removed)),
+            (
+                "Type changed",
+                self.type_changed
+                    .iter()
+                    .map(|c| format!("{}: {} -> {}", code(&c.key), code(&c.old), code(&c.new)))
+                    .collect(),
+            ),
+            ("Deprecated", keys(&self.deprecated)),
+            (
+                "Renamed",
+                self.renamed
+                    .iter()
+                    .map(|r| format!("{} -> {}", code(&r.key), code(&r.renamed_to)))
+                    .collect(),
+            ),
+        ];
+        sections
+            .into_iter()
+            .filter(|(_, lines)| !lines.is_empty())
+            .map(|(title, lines)| (format!("{title} ({})", lines.len()), lines))
+            .collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "No changes\n".to_owned();
+        }
+        let mut text = String::new();
+        for (title, lines) in self.sections(|s| s.to_owned()) {
+            text.push_str(&format!("{title}\n"));
+            for line in lines {
+                text.push_str(&format!("  {line}\n"));
+            }
+        }
+        text
+    }
+
+    pub fn to_markdown(&self) -> String {
+        if self.is_empty() {
+            return "No changes\n".to_owned();
+        }
+        let mut sections = vec![];
+        for (title, lines) in self.sections(|s| format!("`{s}`")) {
+            let items: Vec<String> = lines.iter().map(|line| format!("- {line}")).collect();
+            sections.push(format!("## {title}\n\n{}\n", items.join("\n")));
+        }
+        sections.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semconv::attribute_map;
+
+    #[test]
+    fn test_diff() {
+        let old = attribute_map(&[
+            ("http.method", "id: method\ntype: string"),
+            ("http.status_code", "id: status_code\ntype: int"),
+            ("http.flavor", "id: flavor\ntype: string"),
+            ("net.peer.name", "id: peer.name\ntype: string"),
+        ]);
+        let new = attribute_map(&[
+            (
+                "http.method",
+                "id: method\ntype: string\n\
+                 deprecated:\n  reason: renamed\n  renamed_to: http.request.method",
+            ),
+            ("http.status_code", "id: status_code\ntype: string"),
+            (
+                "http.flavor",
+                "id: flavor\ntype: string\ndeprecated:\n  reason: obsoleted",
+            ),
+            ("http.request.method", "id: request.method\ntype: string"),
+        ]);
+
+        let diff = Diff::new(&old, &new);
+        assert_eq!(diff.added, vec!["http.request.method"]);
+        assert_eq!(diff.removed, vec!["net.peer.name"]);
+        assert_eq!(
+            diff.type_changed,
+            vec![TypeChange {
+                key: "http.status_code".to_owned(),
+                old: "int".to_owned(),
+                new: "string".to_owned(),
+            }]
+        );
+        assert_eq!(diff.deprecated, vec!["http.flavor"]);
+        assert_eq!(
+            diff.renamed,
+            vec![Rename {
+                key: "http.method".to_owned(),
+                renamed_to: "http.request.method".to_owned(),
+            }]
+        );
+        assert!(diff
+            .to_markdown()
+            .contains("## Type changed (1)\n\n- `http.status_code`: `int` -> `string`\n"));
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let old = attribute_map(&[("http.route", "id: route\ntype: string")]);
+        let diff = Diff::new(&old, &old);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_text(), "No changes\n");
+    }
+}
@@ -0,0 +1,195 @@
+use std::{fs, path::Path};
+
+use askama::Template;
+use serde::Serialize;
+
+use crate::{
+    coverage,
+    data::{self, Node},
+    datasets_template, groups_template, mismatches_template, node_template, registries_template,
+    registry_template,
+    semconv::Attribute,
+    snapshot::Snapshot,
+    undefined_template, Asset, DatasetTemplate, GroupTemplate, Model, ProblemsTemplate,
+    TreeTemplate,
+};
+
+#[derive(Template)]
+#[template(path = "export.html")]
+struct ExportTemplate {
+    tree: String,
+}
+
+/// An attribute in the client-side search index.
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    key: &'a str,
+    parent: String,
+    registry_name: &'a str,
+    brief: &'a str,
+}
+
+/// The paths of every tree node with children, i.e. every node page.
+fn branches<'a>(node: &'a Node<Attribute>, paths: &mut Vec<&'a str>) {
+    for child in node.children.values() {
+        if !child.children.is_empty() {
+            paths.push(&child.path);
+            branches(child, paths);
+        }
+    }
+}
+
+/// Render the explorer to static files in `out`: an index page with the
+/// tree, a fragment for every page, and a search index. Honeycomb isn't
+/// queried, so the used by and observed values links are left out. Returns
+/// the number of pages written.
+pub fn export(model: &Model, snapshot: &Snapshot, out: &Path) -> anyhow::Result<usize> {
+    let mut pages = vec![(
+        "node/root".to_owned(),
+        node_template(&model.db, "root".to_owned(), false).render()?,
+    )];
+    let mut paths = vec![];
+    branches(&model.db, &mut paths);
+    for path in paths {
+        let page = node_template(&model.db, path.to_owned(), false).render()?;
+        pages.push((format!("node/{path}"), page));
+    }
+    pages.push(("groups".to_owned(), groups_template(model, false).render()?));
+    for (id, group) in &model.groups {
+        let page = GroupTemplate {
+            group: Some(group.clone()),
+            id: id.clone(),
+            live: false,
+        };
+        pages.push((format!("group/{id}"), page.render()?));
+    }
+    pages.push((
+        "registries".to_owned(),
+        registries_template(model, false).render()?,
+    ));
+    for name in model.registries.keys() {
+        let page = registry_template(model, name.clone(), false).render()?;
+        pages.push((format!("registry/{name}"), page));
+    }
+    pages.push((
+        "datasets".to_owned(),
+        datasets_template(model, snapshot, false).render()?,
+    ));
+    for slug in snapshot.datasets.keys() {
+        let page = DatasetTemplate {
+            dataset: coverage::dataset_conformance(slug, snapshot, &model.attribute_map),
+            slug: slug.clone(),
+            live: false,
+        };
+        pages.push((format!("dataset/{slug}"), page.render()?));
+    }
+    pages.push((
+        "undefined".to_owned(),
+        undefined_template(model, snapshot, false).render()?,
+    ));
+    pages.push((
+        "mismatches".to_owned(),
+        mismatches_template(model, false).render()?,
+    ));
+    let problems = ProblemsTemplate {
+        problems: model.problems.clone(),
+    };
+    pages.push(("problems".to_owned(), problems.render()?));
+
+    for (page, html) in &pages {
+        let path = out.join(format!("{page}.html"));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, html)?;
+    }
+
+    let index = ExportTemplate {
+        tree: TreeTemplate {
+            node: &model.db,
+            live: false,
+        }
+        .render()?,
+    };
+    // the favicon is next to the index rather than at the server's root
+    let index = index.render()?.replace("href=\"/dist/", "href=\"dist/");
+    fs::write(out.join("index.html"), index)?;
+    if let Some(favicon) = Asset::get("favicon.ico") {
+        fs::create_dir_all(out.join("dist"))?;
+        fs::write(out.join("dist/favicon.ico"), favicon.data)?;
+    }
+
+    let mut keys: Vec<&String> = model.attribute_map.keys().collect();
+    keys.sort();
+    let search: Vec<SearchEntry> = keys
+        .into_iter()
+        .map(|key| {
+            let attribute = &model.attribute_map[key];
+            SearchEntry {
+                key,
+                parent: data::parent_path(key),
+                registry_name: attribute.registry_name.as_deref().unwrap_or(""),
+                brief: attribute.brief.as_deref().unwrap_or(""),
+            }
+        })
+        .collect();
+    fs::write(out.join("search.json"), serde_json::to_string(&search)?)?;
+
+    Ok(pages.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{semconv::SemanticConventions, source::ModelSource};
+
+    #[test]
+    fn test_export() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("model")).unwrap();
+        fs::write(
+            dir.join("model/http.yaml"),
+            r#"
+            groups:
+              - id: registry.http
+                type: attribute_group
+                brief: HTTP
+                prefix: http.request
+                attributes:
+                  - id: method
+                    type: string
+                    brief: The request method.
+                    examples: ["GET"]
+            "#,
+        )
+        .unwrap();
+        let source = ModelSource::parse(dir.join("model").to_str().unwrap()).unwrap();
+        let sc = SemanticConventions::new(&[("t".to_owned(), source)]).unwrap();
+        let model = Model::new(sc, &Snapshot::default());
+
+        let out = dir.join("site");
+        export(&model, &Snapshot::default(), &out).unwrap();
+        let read = |path: &str| fs::read_to_string(out.join(path)).unwrap();
+        assert!(read("index.html").contains(r##"href="#/node/http""##));
+        assert!(read("node/http.request.html").contains("The request method."));
+        assert!(read("group/registry.http.html").contains("registry.http"));
+        assert!(read("search.json").contains(r#""parent":"http.request""#));
+        assert!(read("groups.html").contains(r##"href="#/group/registry.http""##));
+        assert!(read("group/registry.http.html").contains(r##"href="#/node/http.request""##));
+
+        // no page links to a server route
+        let mut dirs = vec![out.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|e| e == "html") {
+                    let html = fs::read_to_string(&path).unwrap();
+                    assert!(!html.contains("hx-push-url"), "{}", path.display());
+                }
+            }
+        }
+    }
+}
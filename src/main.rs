@@ -1,13 +1,35 @@
+mod api;
+mod catalogue;
+mod codegen;
+mod coverage;
 mod data;
+mod diff;
+mod export;
+mod manifest;
+mod otlp;
+mod query;
+mod receiver;
+mod search;
 mod semconv;
+mod snapshot;
+mod source;
+mod validate;
+mod watch;
 
-use std::{collections::BTreeMap, path, vec};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    vec,
+};
 
 use anyhow::Context;
 use askama::Template;
 use askama_axum::IntoResponse;
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
     http::{
         header::{self, HeaderMap},
         StatusCode, Uri,
@@ -16,17 +38,45 @@ use axum::{
     routing::get,
     Router,
 };
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use coverage::{DatasetConformance, EnumValue, UndefinedColumn};
 use data::Node;
+use diff::Diff;
+use futures::StreamExt;
 use honeycomb_client::honeycomb::HoneyComb;
 use rust_embed::RustEmbed;
-use semconv::{Attribute, Examples, PrimitiveType, SemanticConventions, Type::Simple};
+use search::{SearchIndex, SearchResult};
+use semconv::{
+    Attribute, Examples, Group, PrimitiveType, Problem, Registry, SemanticConventions, Stability,
+    Type::Simple, TypeMismatch,
+};
+use serde::Deserialize;
 use serde_json::json;
+use snapshot::Snapshot;
+use source::ModelSource;
+use tokio::sync::broadcast;
 
 #[derive(Template)]
 #[template(path = "index.html")]
 struct IndexTemplate {
     node: String,
+    path: String,
+}
+
+#[derive(Template)]
+#[template(path = "tree.html")]
+struct TreeTemplate<'a> {
+    node: &'a Node<Attribute>,
+    live: bool,
+}
+
+impl<'a> TreeTemplate<'a> {
+    fn subtree(&self, node: &'a Node<Attribute>) -> Self {
+        TreeTemplate {
+            node,
+            live: self.live,
+        }
+    }
 }
 
 #[derive(Template)]
@@ -36,6 +86,9 @@ struct NodeTemplate {
     level_parts: Vec<String>,
     level_links: Vec<String>,
     nodes: Vec<Node<Attribute>>,
+    /// Whether the explorer is served, so Honeycomb can be queried and
+    /// links are htmx requests; false in a static export.
+    live: bool,
 }
 
 #[derive(Template)]
@@ -43,6 +96,7 @@ struct NodeTemplate {
 struct UsedByTemplate {
     attribute: String,
     datasets: Vec<String>,
+    live: bool,
 }
 
 #[derive(Template)]
@@ -51,34 +105,472 @@ struct SuffixUsedByTemplate {
     attribute: String,
     suffix: String,
     datasets: Vec<String>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "search.html")]
+struct SearchTemplate {
+    query: String,
+    results: Vec<SearchResult>,
+}
+
+#[derive(Template)]
+#[template(path = "group.html")]
+struct GroupTemplate {
+    group: Option<Group>,
+    id: String,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "groups.html")]
+struct GroupsTemplate {
+    groups_by_type: BTreeMap<String, Vec<Group>>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "undefined.html")]
+struct UndefinedTemplate {
+    has_datasets: bool,
+    column_count: usize,
+    namespaces: BTreeMap<String, Vec<UndefinedColumn>>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "datasets.html")]
+struct DatasetsTemplate {
+    datasets: Vec<DatasetConformance>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "dataset.html")]
+struct DatasetTemplate {
+    dataset: Option<DatasetConformance>,
+    slug: String,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "enum_values.html")]
+struct EnumValuesTemplate {
+    window: String,
+    values: Vec<EnumValue>,
+    closed: bool,
+    failed: Vec<String>,
+}
+
+#[derive(Template)]
+#[template(path = "diff.html")]
+struct DiffTemplate {
+    diff: Option<Diff>,
+}
+
+#[derive(Template)]
+#[template(path = "problems.html")]
+struct ProblemsTemplate {
+    problems: Vec<Problem>,
+}
+
+#[derive(Template)]
+#[template(path = "registries.html")]
+struct RegistriesTemplate {
+    /// Each registry with its attribute and group counts.
+    registries: Vec<(String, Registry, usize, usize)>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "registry.html")]
+struct RegistryTemplate {
+    name: String,
+    registry: Option<Registry>,
+    attribute_count: usize,
+    groups: Vec<String>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "mismatches.html")]
+struct MismatchesTemplate {
+    attributes: Vec<(String, Attribute)>,
+    live: bool,
+}
+
+#[derive(Template)]
+#[template(path = "validate.html")]
+struct ValidateTemplate {}
+
+#[derive(Template)]
+#[template(path = "violations.html")]
+struct ViolationsTemplate {
+    error: Option<String>,
+    attribute_count: usize,
+    /// All the violations found; only the first [`MAX_VIOLATIONS`] are listed.
+    violation_count: usize,
+    violations: Vec<validate::Violation>,
+}
+
+impl ViolationsTemplate {
+    fn error(error: impl ToString) -> Self {
+        ViolationsTemplate {
+            error: Some(error.to_string()),
+            attribute_count: 0,
+            violation_count: 0,
+            violations: vec![],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EnumValuesParams {
+    window: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CatalogueParams {
+    prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FilterParams {
+    stability: Option<Stability>,
+}
+
+impl FilterParams {
+    /// Hide definitions that don't match the requested stability.
+    fn apply<'a>(&self, db: &'a Node<Attribute>) -> Cow<'a, Node<Attribute>> {
+        match &self.stability {
+            Some(stability) => {
+                Cow::Owned(db.filter(&|attribute: &Attribute| {
+                    attribute.stability.as_ref() == Some(stability)
+                }))
+            }
+            None => Cow::Borrowed(db),
+        }
+    }
+}
+
+/// Everything built from the model directories, replaced on reload.
+struct Model {
+    db: Node<Attribute>,
+    search: SearchIndex,
+    groups: BTreeMap<String, Group>,
+    attribute_map: HashMap<String, Attribute>,
+    problems: Vec<Problem>,
+    registries: BTreeMap<String, Registry>,
+}
+
+impl Model {
+    fn new(mut sc: SemanticConventions, snapshot: &Snapshot) -> Self {
+        // augment the attributes with the honeycomb data
+        add_hny_to_attributes(snapshot, &mut sc);
+
+        // add all the attributes to the tree
+        let mut root = Node::new("root".to_string(), "".to_owned(), None);
+        let mut keys: Vec<_> = sc.attribute_map.keys().collect();
+        keys.sort();
+        for k in keys {
+            let value = &sc.attribute_map[k];
+            let tag = value
+                .registry_name
+                .as_ref()
+                .map(|s| s.to_owned())
+                .unwrap_or("".to_owned());
+            root.add_node(k, &tag, Some(value.clone()));
+            if let Some(stability) = &value.stability {
+                root.add_count(k, &stability.to_string());
+            }
+        }
+
+        // index the attributes for full-text search
+        let search = SearchIndex::new(&sc.attribute_map);
+
+        Model {
+            db: root,
+            search,
+            groups: sc.groups,
+            attribute_map: sc.attribute_map,
+            problems: sc.problems,
+            registries: sc.registries,
+        }
+    }
+
+    /// The number of attributes and the ids of the groups defined in a registry.
+    fn registry_contents(&self, name: &str) -> (usize, Vec<String>) {
+        let attributes = self
+            .attribute_map
+            .values()
+            .filter(|attribute| attribute.registry_name.as_deref() == Some(name))
+            .count();
+        let groups = self
+            .groups
+            .values()
+            .filter(|group| group.registry_name.as_deref() == Some(name))
+            .map(|group| group.id.clone())
+            .collect();
+        (attributes, groups)
+    }
 }
 
 #[derive(Clone)]
 struct AppState {
-    db: Node<Attribute>,
+    model: Arc<RwLock<Arc<Model>>>,
+    snapshot: Arc<RwLock<Arc<Snapshot>>>,
     hc: Option<HoneyComb>,
+    queries: Option<query::Queries>,
+    reloads: broadcast::Sender<()>,
+    /// The attributes of the older model given with --diff-against.
+    diff_against: Option<Arc<HashMap<String, Attribute>>>,
+}
+
+impl AppState {
+    /// The current model. Handlers hold on to it for the whole request so a
+    /// reload can't swap it out part way through.
+    fn model(&self) -> Arc<Model> {
+        self.model.read().unwrap().clone()
+    }
+
+    /// The current dataset columns, including those seen by the OTLP receiver.
+    fn snapshot(&self) -> Arc<Snapshot> {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    /// The changes from the --diff-against model to the current one.
+    fn diff(&self) -> Option<Diff> {
+        let old = self.diff_against.as_ref()?;
+        Some(Diff::new(old, &self.model().attribute_map))
+    }
 }
 
 #[derive(Parser, Debug)]
-#[command(author, version)]
+#[command(author, version, subcommand_negates_reqs = true)]
 /// Honey Explore
 ///
 /// Explore OpenTelemetry Semantic Convention compatible models in a web browser.
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Model paths
     ///
     /// Provide one or more paths to the root of semantic convention
     /// model directories. Each path must be prefixed with a single character
     /// representing the registry followed by a double colon. For example:
     ///    🔭::/otel/semantic-conventions/model
+    ///
+    /// To read a directory in a local git repository as of a tag, branch or
+    /// commit, without checking it out, follow the path with @ and the ref:
+    ///    🤖::/my-org/model@v1.4.0
+    ///
+    /// A path may also be a single yaml file, or a .tar.gz or .zip archive
+    /// read in memory. Follow an archive with a directory inside it to read
+    /// only that directory, skipping the archive's top-level directory:
+    ///    🔭::/downloads/semantic-conventions-1.28.0.tar.gz/model
     #[arg(short, long, required = true, num_args(1..))]
     model: Vec<String>,
 
+    /// Resolve dependencies
+    ///
+    /// Also load the registries listed as dependencies in each model's
+    /// registry_manifest.yaml, when they are local paths. A dependency's
+    /// registry character is the first character of its name.
+    #[arg(long)]
+    resolve_dependencies: bool,
+
     /// Address
     ///
     /// TCP Address to listen on.
     #[arg(short, long, default_value_t = String::from("127.0.0.1:3000"))]
     addr: String,
+
+    /// OTLP receiver address
+    ///
+    /// Also receive OTLP/HTTP traces, metrics and logs, as JSON or protobuf,
+    /// on this address, e.g. 127.0.0.1:4318. Each service.name is shown as a
+    /// dataset of the attribute keys and value types it sends, so attributes
+    /// are marked as used without a Honeycomb API key.
+    #[arg(long)]
+    otlp_addr: Option<String>,
+
+    /// Save snapshot
+    ///
+    /// Save the columns read from every Honeycomb dataset to a JSON file.
+    #[arg(long, conflicts_with = "load_snapshot")]
+    save_snapshot: Option<PathBuf>,
+
+    /// Load snapshot
+    ///
+    /// Load the dataset columns from a JSON file saved with --save-snapshot
+    /// instead of reading them from Honeycomb. No API key is required.
+    #[arg(long)]
+    load_snapshot: Option<PathBuf>,
+
+    /// Report undefined columns
+    ///
+    /// Print the Honeycomb columns that match no attribute or template
+    /// prefix, grouped by namespace, and exit.
+    #[arg(long)]
+    report_undefined: bool,
+
+    /// Strict
+    ///
+    /// Fail to start if an attribute is defined more than once with a
+    /// different type, brief or examples.
+    #[arg(long)]
+    strict: bool,
+
+    /// Diff against
+    ///
+    /// Paths to an older version of the model, in the same form as --model.
+    /// The diff page lists the changes from it to the current model.
+    #[arg(long, num_args(1..))]
+    diff_against: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two versions of a model
+    ///
+    /// List the attributes added, removed, type-changed, deprecated and
+    /// renamed between the old and new model, and exit.
+    Diff {
+        /// Old model paths, in the same form as --model
+        #[arg(long, required = true, num_args(1..))]
+        old: Vec<String>,
+
+        /// New model paths, in the same form as --model
+        #[arg(long, required = true, num_args(1..))]
+        new: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Export a static site
+    ///
+    /// Render every page of the explorer, with the tree and a client-side
+    /// search index, to static HTML that can be published on any static
+    /// host, and exit. Use --load-snapshot before the subcommand, or
+    /// --honeycomb, to include the Honeycomb data.
+    Export {
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// Output directory
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Read the datasets from Honeycomb, unless --load-snapshot is given
+        #[arg(long)]
+        honeycomb: bool,
+    },
+
+    /// Print the attribute catalogue
+    ///
+    /// Print every attribute's key, type, registry, file, deprecation, brief,
+    /// examples and the datasets using it as a table, and exit. Use
+    /// --load-snapshot before the subcommand, or --honeycomb, to include the
+    /// datasets.
+    Attributes {
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// Only the attributes whose keys start with this prefix, e.g. http.
+        #[arg(long)]
+        prefix: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        format: TableFormat,
+
+        /// Read the datasets from Honeycomb, unless --load-snapshot is given
+        #[arg(long)]
+        honeycomb: bool,
+    },
+
+    /// Generate attribute constants
+    ///
+    /// Print a constant for every attribute name, documented with its brief
+    /// and note, an enum type for every enum attribute, and deprecations in
+    /// the language's own form, and exit.
+    Codegen {
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// Language
+        #[arg(long, value_enum)]
+        lang: Language,
+
+        /// Only the attributes whose keys start with this prefix, e.g. http.
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+
+    /// Validate OTLP/JSON telemetry
+    ///
+    /// Check every attribute of the traces, metrics or logs in an OTLP/JSON
+    /// file against the conventions: that it's defined, has the right type
+    /// and enum value, isn't deprecated and has a key if it's a template.
+    /// Print each violation with its line and exit with an error if there
+    /// are any.
+    Validate {
+        #[command(flatten)]
+        model: ModelArgs,
+
+        /// OTLP/JSON file, one request per document or per line
+        file: PathBuf,
+    },
+}
+
+/// The model of a subcommand that loads one.
+#[derive(clap::Args, Debug)]
+struct ModelArgs {
+    /// Model paths, in the same form as --model
+    #[arg(short, long, required = true, num_args(1..))]
+    model: Vec<String>,
+}
+
+impl Command {
+    fn model_args(&self) -> Option<&ModelArgs> {
+        match self {
+            Command::Diff { .. } => None,
+            Command::Export { model, .. }
+            | Command::Attributes { model, .. }
+            | Command::Codegen { model, .. }
+            | Command::Validate { model, .. } => Some(model),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum Format {
+    Text,
+    Markdown,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum TableFormat {
+    Csv,
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum Language {
+    Rust,
+    Go,
+    Python,
+    Ts,
 }
 
 #[derive(RustEmbed)]
@@ -113,13 +605,11 @@ async fn static_handler(uri: Uri) -> impl IntoResponse {
     StaticFile(path)
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // load configuration
-    dotenv::dotenv().ok();
-    let args = Args::parse();
-    let mut root_dirs = vec![];
-    for path in args.model {
+/// Parse model paths of the form `<registry character>::<directory>`, where
+/// the directory may be followed by `@<git ref>`.
+fn parse_model_paths(paths: &[String]) -> anyhow::Result<Vec<(String, ModelSource)>> {
+    let mut sources = vec![];
+    for path in paths {
         if !path.contains("::") {
             anyhow::bail!(
                 "path must be prefixed with a registry character followed by a double colon"
@@ -133,65 +623,215 @@ async fn main() -> anyhow::Result<()> {
                 registry_name
             );
         }
-        let p = path::Path::new(&split[1]);
-        if !p.is_dir() {
-            anyhow::bail!("{} is not directory", path);
+        sources.push((registry_name.to_owned(), ModelSource::parse(split[1])?));
+    }
+    Ok(sources)
+}
+
+/// Parse the model paths and, if asked, add the registries they depend on.
+fn load_sources(
+    paths: &[String],
+    resolve_dependencies: bool,
+) -> anyhow::Result<Vec<(String, ModelSource)>> {
+    let mut sources = parse_model_paths(paths)?;
+    if resolve_dependencies {
+        for warning in manifest::resolve_dependencies(&mut sources)? {
+            eprintln!("{warning}");
         }
-        root_dirs.push((
-            registry_name.to_owned(),
-            p.canonicalize()?
-                .to_str()
-                .context("invalid path")?
-                .to_owned(),
-        ));
     }
+    Ok(sources)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // load configuration
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    if let Some(Command::Diff { old, new, format }) = &args.command {
+        return print_diff(old, new, format, args.resolve_dependencies);
+    }
+    let model_paths = match args.command.as_ref().and_then(Command::model_args) {
+        Some(model_args) => &model_args.model,
+        None => &args.model,
+    };
+    let sources = load_sources(model_paths, args.resolve_dependencies)?;
     // load semantic conventions
-    let mut sc = SemanticConventions::new(&root_dirs)?;
+    let sc = SemanticConventions::new(&sources)?;
+    print_problems(&sc.problems);
+    let conflicts = sc.conflicts();
+    if !conflicts.is_empty() {
+        if args.strict {
+            anyhow::bail!(
+                "conflicting attribute definitions:\n  {}",
+                conflicts.join("\n  ")
+            );
+        }
+        eprintln!("Conflicting attribute definitions:");
+        for conflict in &conflicts {
+            eprintln!("  {conflict}");
+        }
+    }
 
-    // build the tree
-    let mut root = Node::new("root".to_string(), "".to_owned(), None);
-    let hc = match honeycomb_client::get_honeycomb(&["columns", "createDatasets", "queries"]).await
-    {
-        Ok(hclient) => hclient,
-        Err(e) => {
-            eprintln!("Failed to get honeycomb client: {e}");
-            None
+    // generated code and validation don't depend on the Honeycomb data
+    if let Some(Command::Codegen { lang, prefix, .. }) = &args.command {
+        let attributes = semconv::attributes_with_prefix(&sc.attribute_map, prefix.as_deref());
+        let code = match lang {
+            Language::Rust => codegen::rust(&attributes)?,
+            Language::Go => codegen::go(&attributes)?,
+            Language::Python => codegen::python(&attributes)?,
+            Language::Ts => codegen::typescript(&attributes)?,
+        };
+        print!("{code}");
+        return Ok(());
+    }
+
+    if let Some(Command::Validate { file, .. }) = &args.command {
+        let text = std::fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let attributes = otlp::read_json(&text)?;
+        let violations = validate::validate(&attributes, &sc.attribute_map);
+        for violation in &violations {
+            println!(
+                "{}:{}: {}: {} {}",
+                file.display(),
+                violation.line,
+                violation.context,
+                violation.key,
+                violation.message
+            );
+        }
+        if !violations.is_empty() {
+            anyhow::bail!("{} violations", violations.len());
+        }
+        println!("{} attributes follow the conventions", attributes.len());
+        return Ok(());
+    }
+
+    // reading every dataset takes minutes, so the export and attributes
+    // subcommands only do it when asked
+    let use_honeycomb = match &args.command {
+        Some(Command::Export { honeycomb, .. } | Command::Attributes { honeycomb, .. }) => {
+            *honeycomb
+        }
+        _ => true,
+    };
+    let hc = if use_honeycomb {
+        match honeycomb_client::get_honeycomb(&["columns", "createDatasets", "queries"]).await {
+            Ok(hclient) => hclient,
+            Err(e) => {
+                eprintln!("Failed to get honeycomb client: {e}");
+                None
+            }
         }
+    } else {
+        None
+    };
+
+    // load the dataset columns from a snapshot or, if we have a valid
+    // api-key with enough access permission, fetch them from honeycomb
+    let snapshot = match (&args.load_snapshot, &hc) {
+        (Some(path), _) => Some(Snapshot::load(path)?),
+        (None, Some(client)) => Some(Snapshot::from_honeycomb(client).await?),
+        (None, None) => None,
     };
+    if let Some(path) = &args.save_snapshot {
+        let snapshot = snapshot
+            .as_ref()
+            .context("--save-snapshot requires a Honeycomb API key")?;
+        snapshot.save(path)?;
+        println!("saved snapshot to {}", path.display());
+    }
 
-    // if we have a valid api-key with enough access permission then
-    // fetch all the honeycomb data and augment the attributes
-    if let Some(client) = &hc {
-        add_hny_to_attributes(client, &mut sc).await?;
+    // augment the attributes with the honeycomb data
+    let snapshot = snapshot.unwrap_or_default();
+    let model = Model::new(sc.clone(), &snapshot);
+
+    if args.report_undefined {
+        if snapshot.datasets.is_empty() {
+            anyhow::bail!("--report-undefined requires a Honeycomb API key or --load-snapshot");
+        }
+        report_undefined(&snapshot, &model.attribute_map);
+        return Ok(());
     }
 
-    // add all the attributes to the tree
-    let mut keys: Vec<_> = sc.attribute_map.keys().collect();
-    keys.sort();
-    for k in keys {
-        let value = &sc.attribute_map[k];
-        let tag = value
-            .registry_name
-            .as_ref()
-            .map(|s| s.to_owned())
-            .unwrap_or("".to_owned());
-        root.add_node(k, &tag, Some(value.clone()));
+    if let Some(Command::Export { out, .. }) = &args.command {
+        let pages = export::export(&model, &snapshot, out)?;
+        println!("exported {pages} pages to {}", out.display());
+        return Ok(());
     }
 
-    let state = AppState { db: root, hc };
+    if let Some(Command::Attributes { prefix, format, .. }) = &args.command {
+        let rows = catalogue::rows(&model.attribute_map, prefix.as_deref());
+        match format {
+            TableFormat::Csv => print!("{}", catalogue::to_csv(&rows)?),
+            TableFormat::Markdown => print!("{}", catalogue::to_markdown(&rows)),
+        }
+        return Ok(());
+    }
+
+    let diff_against = if args.diff_against.is_empty() {
+        None
+    } else {
+        let old = SemanticConventions::new(&load_sources(
+            &args.diff_against,
+            args.resolve_dependencies,
+        )?)?;
+        print_problems(&old.problems);
+        Some(Arc::new(old.attribute_map))
+    };
+
+    let (reloads, _) = broadcast::channel(16);
+    let state = AppState {
+        model: Arc::new(RwLock::new(Arc::new(model))),
+        snapshot: Arc::new(RwLock::new(Arc::new(snapshot))),
+        queries: hc.as_ref().map(query::Queries::new),
+        hc,
+        reloads,
+        diff_against,
+    };
+
+    // rebuild the model when the yaml files change or new columns are received
+    let reload = watch::reloader(&sources, sc, state.clone());
+    let _watcher = watch::watch(&sources, reload.clone())?;
+    if let Some(otlp_addr) = &args.otlp_addr {
+        receiver::serve(otlp_addr, state.clone(), reload).await?;
+    }
 
     // build our application with a route
     let app = Router::new()
         .route("/", get(handler))
         .route("/tree/:name", get(tree_handler))
         .route("/node/:name", get(node_handler))
+        .route("/groups", get(groups_handler))
+        .route("/group/:id", get(group_handler))
+        .route("/registries", get(registries_handler))
+        .route("/registry/:name", get(registry_handler))
+        .route("/undefined", get(undefined_handler))
+        .route("/mismatches", get(mismatches_handler))
+        .route("/problems", get(problems_handler))
+        .route("/diff", get(diff_handler))
+        .route("/datasets", get(datasets_handler))
+        .route("/dataset/:slug", get(dataset_handler))
+        .route("/search", get(search_handler))
+        .route(
+            "/validate",
+            // uploaded telemetry can be much larger than the default 2MB limit
+            get(validate_handler)
+                .post(violations_handler)
+                .layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        )
+        .route("/export/attributes.csv", get(catalogue_csv_handler))
+        .route("/export/attributes.md", get(catalogue_markdown_handler))
         .route("/usedby/:name", get(used_by_handler))
         .route("/suffix_usedby/:name/:suffix", get(suffix_used_by_handler))
+        .route("/enumvalues/:name", get(enum_values_handler))
         .route(
             "/hnyexists/:dataset/:column/:suffix",
             get(honeycomb_exists_handler),
         )
+        .route("/events", get(watch::events_handler))
         .route("/dist/*file", get(static_handler))
+        .nest("/api/v1", api::router())
         .with_state(state);
 
     // run it
@@ -206,12 +846,34 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn add_hny_to_attributes(hc: &HoneyComb, sc: &mut SemanticConventions) -> anyhow::Result<()> {
-    let dataset_slugs = hc.get_dataset_slugs(30, None).await?;
-    eprint!("Reading {} datasets ", dataset_slugs.len());
-    hc.process_datasets_columns(30, &dataset_slugs, |dataset, columns| {
-        eprint!(".");
-        for column in columns {
+fn add_hny_to_attributes(snapshot: &Snapshot, sc: &mut SemanticConventions) {
+    for (dataset, columns) in &snapshot.datasets {
+        for column in columns.iter() {
+            // flag columns with a type that can't hold the attribute's values
+            let mismatched = coverage::matching_attribute(&sc.attribute_map, &column.key_name)
+                .and_then(|(key, attribute)| Some((key.clone(), attribute.r#type.as_ref()?)))
+                .map(|(key, r#type)| {
+                    let types: Vec<String> = column
+                        .types()
+                        .filter(|column_type| !coverage::is_compatible(r#type, column_type))
+                        .cloned()
+                        .collect();
+                    (key, types)
+                });
+            if let Some((key, types)) = mismatched {
+                if let Some(attribute) = sc.attribute_map.get_mut(&key) {
+                    for column_type in types {
+                        attribute
+                            .type_mismatches
+                            .get_or_insert_with(Vec::new)
+                            .push(TypeMismatch {
+                                dataset: dataset.clone(),
+                                column: column.key_name.clone(),
+                                column_type,
+                            });
+                    }
+                }
+            }
             if let Some(attribute) = sc.attribute_map.get_mut(&column.key_name) {
                 match attribute.used_by {
                     Some(ref mut used_by) => used_by.push(dataset.clone()),
@@ -239,24 +901,392 @@ async fn add_hny_to_attributes(hc: &HoneyComb, sc: &mut SemanticConventions) ->
                 }
             }
         }
-    })
-    .await?;
-    eprintln!();
+    }
+}
+
+fn report_undefined(snapshot: &Snapshot, attribute_map: &HashMap<String, Attribute>) {
+    let namespaces = coverage::undefined_columns(snapshot, attribute_map);
+    for (namespace, columns) in &namespaces {
+        println!("{namespace} ({} columns)", columns.len());
+        for column in columns {
+            println!("  {}: {}", column.key_name, column.datasets.join(", "));
+        }
+    }
+}
+
+fn print_diff(
+    old: &[String],
+    new: &[String],
+    format: &Format,
+    resolve_dependencies: bool,
+) -> anyhow::Result<()> {
+    let old = SemanticConventions::new(&load_sources(old, resolve_dependencies)?)?;
+    print_problems(&old.problems);
+    let new = SemanticConventions::new(&load_sources(new, resolve_dependencies)?)?;
+    print_problems(&new.problems);
+    let diff = Diff::new(&old.attribute_map, &new.attribute_map);
+    match format {
+        Format::Text => print!("{}", diff.to_text()),
+        Format::Markdown => print!("{}", diff.to_markdown()),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+    }
     Ok(())
 }
 
+fn print_problems(problems: &[Problem]) {
+    if problems.is_empty() {
+        return;
+    }
+    eprintln!("Skipped model files that failed to load, and duplicate groups:");
+    for problem in problems {
+        eprintln!("  {problem}");
+    }
+}
+
 async fn handler() -> impl IntoResponse {
     IndexTemplate {
         node: "root".to_owned(),
+        path: "/node/root".to_owned(),
+    }
+}
+
+/// Check headers to see if this is a full page request or an ajax request
+fn is_full_page_request(headers: &HeaderMap) -> bool {
+    let hx_history_restore_request = headers
+        .get("HX-History-Restore-Request")
+        .and_then(|value| value.to_str().ok())
+        .map(|s| s == "true")
+        .unwrap_or(false);
+
+    hx_history_restore_request || !headers.contains_key("HX-Request")
+}
+
+async fn groups_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/groups".to_owned(),
+        }
+        .into_response();
+    }
+
+    groups_template(&model, true).into_response()
+}
+
+fn groups_template(model: &Model, live: bool) -> GroupsTemplate {
+    let mut groups_by_type: BTreeMap<String, Vec<Group>> = BTreeMap::new();
+    for group in model.groups.values() {
+        groups_by_type
+            .entry(group.get_type().to_owned())
+            .or_default()
+            .push(group.clone());
+    }
+    GroupsTemplate {
+        groups_by_type,
+        live,
+    }
+}
+
+async fn group_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: format!("/group/{id}"),
+        }
+        .into_response();
+    }
+
+    GroupTemplate {
+        group: model.groups.get(&id).cloned(),
+        id,
+        live: true,
+    }
+    .into_response()
+}
+
+async fn registries_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/registries".to_owned(),
+        }
+        .into_response();
+    }
+
+    registries_template(&model, true).into_response()
+}
+
+fn registries_template(model: &Model, live: bool) -> RegistriesTemplate {
+    let registries = model
+        .registries
+        .iter()
+        .map(|(name, registry)| {
+            let (attributes, groups) = model.registry_contents(name);
+            (name.clone(), registry.clone(), attributes, groups.len())
+        })
+        .collect();
+    RegistriesTemplate { registries, live }
+}
+
+async fn registry_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: format!("/registry/{name}"),
+        }
+        .into_response();
+    }
+
+    registry_template(&model, name, true).into_response()
+}
+
+fn registry_template(model: &Model, name: String, live: bool) -> RegistryTemplate {
+    let (attribute_count, groups) = model.registry_contents(&name);
+    RegistryTemplate {
+        registry: model.registries.get(&name).cloned(),
+        name,
+        attribute_count,
+        groups,
+        live,
+    }
+}
+
+async fn undefined_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/undefined".to_owned(),
+        }
+        .into_response();
+    }
+
+    undefined_template(&model, &state.snapshot(), true).into_response()
+}
+
+fn undefined_template(model: &Model, snapshot: &Snapshot, live: bool) -> UndefinedTemplate {
+    let namespaces = coverage::undefined_columns(snapshot, &model.attribute_map);
+    UndefinedTemplate {
+        has_datasets: !snapshot.datasets.is_empty(),
+        column_count: namespaces.values().map(|columns| columns.len()).sum(),
+        namespaces,
+        live,
+    }
+}
+
+async fn mismatches_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/mismatches".to_owned(),
+        }
+        .into_response();
+    }
+
+    mismatches_template(&model, true).into_response()
+}
+
+fn mismatches_template(model: &Model, live: bool) -> MismatchesTemplate {
+    let mut attributes: Vec<(String, Attribute)> = model
+        .attribute_map
+        .iter()
+        .filter(|(_, attribute)| attribute.type_mismatches.is_some())
+        .map(|(key, attribute)| (key.clone(), attribute.clone()))
+        .collect();
+    attributes.sort_by(|a, b| a.0.cmp(&b.0));
+    MismatchesTemplate { attributes, live }
+}
+
+async fn diff_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/diff".to_owned(),
+        }
+        .into_response();
+    }
+
+    DiffTemplate { diff: state.diff() }.into_response()
+}
+
+async fn problems_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/problems".to_owned(),
+        }
+        .into_response();
+    }
+
+    ProblemsTemplate {
+        problems: state.model().problems.clone(),
+    }
+    .into_response()
+}
+
+async fn datasets_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/datasets".to_owned(),
+        }
+        .into_response();
+    }
+
+    datasets_template(&model, &state.snapshot(), true).into_response()
+}
+
+fn datasets_template(model: &Model, snapshot: &Snapshot, live: bool) -> DatasetsTemplate {
+    DatasetsTemplate {
+        datasets: snapshot
+            .datasets
+            .keys()
+            .filter_map(|dataset| {
+                coverage::dataset_conformance(dataset, snapshot, &model.attribute_map)
+            })
+            .collect(),
+        live,
+    }
+}
+
+async fn dataset_handler(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let model = state.model();
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: format!("/dataset/{slug}"),
+        }
+        .into_response();
+    }
+
+    DatasetTemplate {
+        dataset: coverage::dataset_conformance(&slug, &state.snapshot(), &model.attribute_map),
+        slug,
+        live: true,
+    }
+    .into_response()
+}
+
+async fn catalogue_csv_handler(
+    State(state): State<AppState>,
+    Query(params): Query<CatalogueParams>,
+) -> Response {
+    let rows = catalogue::rows(&state.model().attribute_map, params.prefix.as_deref());
+    match catalogue::to_csv(&rows) {
+        Ok(csv) => ([(header::CONTENT_TYPE, "text/csv; charset=utf-8")], csv).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn catalogue_markdown_handler(
+    State(state): State<AppState>,
+    Query(params): Query<CatalogueParams>,
+) -> Response {
+    let rows = catalogue::rows(&state.model().attribute_map, params.prefix.as_deref());
+    (
+        [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+        catalogue::to_markdown(&rows),
+    )
+        .into_response()
+}
+
+async fn validate_handler(headers: HeaderMap) -> Response {
+    if is_full_page_request(&headers) {
+        return IndexTemplate {
+            node: "root".to_owned(),
+            path: "/validate".to_owned(),
+        }
+        .into_response();
+    }
+
+    ValidateTemplate {}.into_response()
+}
+
+/// The most violations listed on the validate page, so a large upload
+/// doesn't render a huge table.
+const MAX_VIOLATIONS: usize = 1000;
+
+async fn violations_handler(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    // a chosen file takes precedence over pasted text
+    let (mut file, mut text) = (Bytes::new(), Bytes::new());
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return ViolationsTemplate::error(e),
+        };
+        let name = field.name().unwrap_or_default().to_owned();
+        match (name.as_str(), field.bytes().await) {
+            ("file", Ok(bytes)) => file = bytes,
+            ("otlp", Ok(bytes)) => text = bytes,
+            (_, Ok(_)) => {}
+            (_, Err(e)) => return ViolationsTemplate::error(e),
+        }
+    }
+    let body = if file.is_empty() { text } else { file };
+
+    // parsing and checking a large upload would hold up the runtime
+    let model = state.model();
+    let result = tokio::task::spawn_blocking(move || {
+        let attributes = otlp::read_json(std::str::from_utf8(&body)?)?;
+        let violations = validate::validate(&attributes, &model.attribute_map);
+        anyhow::Ok((attributes.len(), violations))
+    })
+    .await;
+    match result {
+        Ok(Ok((attribute_count, mut violations))) => {
+            let violation_count = violations.len();
+            violations.truncate(MAX_VIOLATIONS);
+            ViolationsTemplate {
+                error: None,
+                attribute_count,
+                violation_count,
+                violations,
+            }
+        }
+        Ok(Err(e)) => ViolationsTemplate::error(e),
+        Err(e) => ViolationsTemplate::error(e),
     }
 }
 
+async fn search_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> impl IntoResponse {
+    let model = state.model();
+    let query = params.q.unwrap_or_default();
+    let results = model.search.search(&query);
+    SearchTemplate { query, results }
+}
+
 async fn used_by_handler(
     State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> impl IntoResponse {
+    let model = state.model();
     let mut datasets = vec![];
-    if let Some(node) = state.db.get_node(&name) {
+    if let Some(node) = model.db.get_node(&name) {
         if let Some(attribute) = &node.value {
             if let Some(used_by) = &attribute.used_by {
                 datasets.extend_from_slice(used_by);
@@ -266,6 +1296,7 @@ async fn used_by_handler(
     UsedByTemplate {
         attribute: name,
         datasets,
+        live: true,
     }
 }
 
@@ -273,8 +1304,9 @@ async fn suffix_used_by_handler(
     State(state): State<AppState>,
     Path((name, suffix)): Path<(String, String)>,
 ) -> impl IntoResponse {
+    let model = state.model();
     let mut datasets = vec![];
-    if let Some(node) = state.db.get_node(&name) {
+    if let Some(node) = model.db.get_node(&name) {
         if let Some(attribute) = &node.value {
             if let Some(suffixes) = &attribute.template_suffixes {
                 if let Some(used_by) = suffixes.get(&suffix) {
@@ -287,19 +1319,86 @@ async fn suffix_used_by_handler(
         attribute: name,
         suffix,
         datasets,
+        live: true,
+    }
+}
+
+/// The most enum value queries to run at once.
+const MAX_QUERIES: usize = 4;
+
+async fn enum_values_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<EnumValuesParams>,
+) -> Response {
+    let model = state.model();
+    let Some(queries) = &state.queries else {
+        return "<small>A Honeycomb API key is needed to query values.</small>".into_response();
+    };
+    let window = params.window.unwrap_or_else(|| "2h".to_owned());
+    let Some(time_range) = query::parse_window(&window) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Unknown time window {window}, use e.g. 30m, 2h or 7d"),
+        )
+            .into_response();
+    };
+    let Some(attribute) = model
+        .db
+        .get_node(&name)
+        .and_then(|node| node.value.as_ref())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(semconv::Type::Complex(complex)) = &attribute.r#type else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    // run a breakdown query on every dataset using the column, a few at a
+    // time to stay within Honeycomb's rate limits
+    let datasets = attribute.used_by.clone().unwrap_or_default();
+    let results: Vec<_> = futures::stream::iter(datasets)
+        .map(|dataset| async {
+            let result = queries.value_counts(&dataset, &name, time_range).await;
+            (dataset, result)
+        })
+        .buffer_unordered(MAX_QUERIES)
+        .collect()
+        .await;
+    let mut observed = BTreeMap::new();
+    let mut failed = vec![];
+    for (dataset, result) in results {
+        match result {
+            Ok(values) => {
+                observed.insert(dataset, values);
+            }
+            Err(e) => {
+                eprintln!("Failed to query {name} values in {dataset}: {e}");
+                failed.push(dataset);
+            }
+        }
+    }
+    failed.sort();
+    EnumValuesTemplate {
+        window,
+        values: coverage::enum_conformance(complex, &observed),
+        closed: !complex.allow_custom_values,
+        failed,
     }
+    .into_response()
 }
 
 async fn honeycomb_exists_handler(
     State(state): State<AppState>,
     Path((dataset, column, suffix)): Path<(String, String, String)>,
 ) -> Response {
+    let model = state.model();
     fn trigger(url: String) -> Response {
         ([("HX-Trigger", json!({"openWindow":url}).to_string())], "").into_response()
     }
 
     if let Some(hc) = &state.hc {
-        if let Some(node) = state.db.get_node(&column) {
+        if let Some(node) = model.db.get_node(&column) {
             if let Some(value) = node.value.as_ref() {
                 if let Some(column_type) = &value.r#type {
                     match column_type {
@@ -349,11 +1448,13 @@ async fn honeycomb_exists_handler(
 async fn tree_handler(
     State(state): State<AppState>,
     Path(name): Path<String>,
+    Query(filter): Query<FilterParams>,
 ) -> impl IntoResponse {
-    if let Some(node) = state.db.get_node(&name) {
-        node.clone()
-    } else {
-        state.db.clone()
+    let model = state.model();
+    let db = filter.apply(&model.db);
+    TreeTemplate {
+        node: db.get_node(&name).unwrap_or(&db),
+        live: true,
     }
     .into_response()
 }
@@ -361,37 +1462,40 @@ async fn tree_handler(
 async fn node_handler(
     State(state): State<AppState>,
     Path(name): Path<String>,
+    Query(filter): Query<FilterParams>,
     headers: HeaderMap,
 ) -> Response {
-    // check headers to see if this is a full page request or an ajax request
-    let hx_history_restore_request = headers
-        .get("HX-History-Restore-Request")
-        .and_then(|value| value.to_str().ok())
-        .map(|s| s == "true")
-        .unwrap_or(false);
-
-    if hx_history_restore_request || !headers.contains_key("HX-Request") {
+    let model = state.model();
+    if is_full_page_request(&headers) {
         // Handle the case where HX-History-Restore-Request is true
-        return IndexTemplate { node: name }.into_response();
+        return IndexTemplate {
+            path: format!("/node/{name}"),
+            node: name,
+        }
+        .into_response();
     }
 
+    node_template(&filter.apply(&model.db), name, true).into_response()
+}
+
+/// The page listing a tree node's children, `root` or a dotted path.
+fn node_template(db: &Node<Attribute>, name: String, live: bool) -> NodeTemplate {
     if name == "root" {
         return NodeTemplate {
             level: name.clone(),
             level_parts: vec![name.clone()],
             level_links: vec![name.clone()],
-            nodes: state
-                .db
+            nodes: db
                 .children
                 .values()
                 .cloned()
                 .collect::<Vec<Node<Attribute>>>(),
-        }
-        .into_response();
+            live,
+        };
     }
     if name.starts_with("root.") {
         let name = name.trim_start_matches("root.");
-        if let Some(node) = state.db.get_node(name) {
+        if let Some(node) = db.get_node(name) {
             let level_parts = name.split('.').map(|s| s.to_owned()).collect();
             let level_links = get_links(&level_parts);
             return NodeTemplate {
@@ -403,13 +1507,13 @@ async fn node_handler(
                     .values()
                     .cloned()
                     .collect::<Vec<Node<Attribute>>>(),
-            }
-            .into_response();
+                live,
+            };
         }
     }
     let level_parts = name.split('.').map(|s| s.to_owned()).collect();
     let level_links = get_links(&level_parts);
-    if let Some(node) = state.db.get_node(&name) {
+    if let Some(node) = db.get_node(&name) {
         NodeTemplate {
             level: name.clone(),
             level_parts,
@@ -419,16 +1523,16 @@ async fn node_handler(
                 .values()
                 .cloned()
                 .collect::<Vec<Node<Attribute>>>(),
+            live,
         }
-        .into_response()
     } else {
         NodeTemplate {
             level: name.clone(),
             level_parts,
             level_links,
             nodes: vec![],
+            live,
         }
-        .into_response()
     }
 }
 
@@ -0,0 +1,242 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::source::ModelSource;
+
+/// The metadata from a registry's `registry_manifest.yaml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub description: Option<String>,
+    pub semconv_version: Option<String>,
+    pub schema_base_url: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Another registry this one builds on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    pub registry_path: String,
+}
+
+pub fn is_manifest(defined_in: &str) -> bool {
+    matches!(
+        defined_in,
+        "registry_manifest.yaml" | "registry_manifest.yml"
+    )
+}
+
+impl Manifest {
+    /// Read the manifest at the root of a model, if it has one.
+    pub fn read(source: &ModelSource) -> anyhow::Result<Option<Self>> {
+        let Some(defined_in) = source.files()?.into_iter().find(|f| is_manifest(f)) else {
+            return Ok(None);
+        };
+        Ok(Some(Self::open(source, &defined_in)?))
+    }
+
+    /// Read the manifest at `defined_in`, already known to be in the model.
+    pub fn open(source: &ModelSource, defined_in: &str) -> anyhow::Result<Self> {
+        Ok(serde_yaml::from_reader(source.open(defined_in)?)?)
+    }
+}
+
+impl Dependency {
+    /// The registry character for a resolved dependency: the first character
+    /// of its name.
+    pub fn registry_name(&self) -> String {
+        self.name.chars().take(1).collect()
+    }
+
+    /// The model path of a dependency on the local file system, resolved
+    /// against the directory of the manifest declaring it. Paths using the
+    /// `archive.zip[model]` form read the directory inside the archive.
+    pub fn local_path(&self, source: &ModelSource) -> Option<String> {
+        if self.registry_path.contains("://") || self.registry_path.starts_with("git@") {
+            return None;
+        }
+        let registry_path = match self.registry_path.strip_suffix(']') {
+            Some(path) => path.replacen('[', "/", 1),
+            None => self.registry_path.clone(),
+        };
+        let path = Path::new(&registry_path);
+        if path.is_absolute() {
+            return Some(registry_path);
+        }
+        let root = source.local_root()?;
+        Some(root.join(path).to_string_lossy().into_owned())
+    }
+}
+
+/// Add the dependencies declared in each model's manifest, and theirs, to
+/// the sources. Only dependencies on the local file system can be resolved;
+/// the others are returned as warnings. The dependencies of a model read at
+/// a git ref are read at the same ref. A dependency whose registry
+/// character is already used by another model is an error.
+pub fn resolve_dependencies(
+    sources: &mut Vec<(String, ModelSource)>,
+) -> anyhow::Result<Vec<String>> {
+    let mut warnings = vec![];
+    let mut i = 0;
+    while i < sources.len() {
+        let source = sources[i].1.clone();
+        i += 1;
+        let manifest = match Manifest::read(&source) {
+            Ok(Some(manifest)) => manifest,
+            Ok(None) => continue,
+            Err(e) => {
+                warnings.push(format!("Failed to read the manifest of {source}: {e}"));
+                continue;
+            }
+        };
+        for dependency in &manifest.dependencies {
+            let Some(path) = dependency.local_path(&source) else {
+                warnings.push(format!(
+                    "Skipping dependency {} of {}: only local paths can be resolved, add {} with --model instead",
+                    dependency.name, manifest.name, dependency.registry_path
+                ));
+                continue;
+            };
+            let parsed = match &source {
+                ModelSource::Git { git_ref, .. } if Path::new(&path).is_dir() => {
+                    ModelSource::parse(&format!("{path}@{git_ref}"))
+                }
+                ModelSource::Git { git_ref, .. } => Err(anyhow::anyhow!(
+                    "{path} is not a directory that can be read at {git_ref}"
+                )),
+                _ => ModelSource::parse(&path),
+            };
+            match parsed {
+                Ok(resolved) => {
+                    if sources.iter().any(|(_, s)| s.same_location(&resolved)) {
+                        continue;
+                    }
+                    let registry_name = dependency.registry_name();
+                    if let Some((_, other)) = sources.iter().find(|(r, _)| *r == registry_name) {
+                        anyhow::bail!(
+                            "Dependency {} of {} would use the registry character {registry_name}, which is already used by {other}. \
+                             Add {} with --model and a different character instead",
+                            dependency.name,
+                            manifest.name,
+                            dependency.registry_path
+                        );
+                    }
+                    sources.push((registry_name, resolved));
+                }
+                Err(e) => warnings.push(format!(
+                    "Skipping dependency {} of {}: {e}",
+                    dependency.name, manifest.name
+                )),
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("acme")).unwrap();
+        std::fs::create_dir_all(dir.join("otel/model")).unwrap();
+        std::fs::write(
+            dir.join("acme/registry_manifest.yaml"),
+            r#"
+            name: acme
+            description: Acme's registry.
+            semconv_version: 0.1.0
+            schema_base_url: https://acme.com/schemas/
+            dependencies:
+              - name: otel
+                registry_path: ../otel/model
+              - name: remote
+                registry_path: https://github.com/open-telemetry/semantic-conventions/archive/refs/tags/v1.26.0.zip[model]
+            "#,
+        )
+        .unwrap();
+
+        let acme = ModelSource::parse(dir.join("acme").to_str().unwrap()).unwrap();
+        let manifest = Manifest::read(&acme).unwrap().unwrap();
+        assert_eq!(manifest.name, "acme");
+        assert_eq!(manifest.semconv_version.as_deref(), Some("0.1.0"));
+        assert_eq!(manifest.dependencies.len(), 2);
+
+        let mut sources = vec![("a".to_owned(), acme.clone())];
+        let warnings = resolve_dependencies(&mut sources).unwrap();
+
+        // the dependency's character is taken by another model
+        let mut clashing = vec![
+            ("a".to_owned(), acme),
+            (
+                "o".to_owned(),
+                ModelSource::parse(dir.to_str().unwrap()).unwrap(),
+            ),
+        ];
+        let error = resolve_dependencies(&mut clashing).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Dependency otel of acme would use the registry character o"));
+
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[1].0, "o");
+        assert!(sources[1].1.to_string().ends_with("otel/model"));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Skipping dependency remote of acme"));
+    }
+
+    #[test]
+    fn test_resolve_dependencies_at_git_ref() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        std::fs::create_dir_all(repo.join("acme")).unwrap();
+        std::fs::create_dir_all(repo.join("otel")).unwrap();
+        std::fs::write(
+            repo.join("acme/registry_manifest.yaml"),
+            r#"
+            name: acme
+            dependencies:
+              - name: otel
+                registry_path: ../otel
+              - name: gone
+                registry_path: ../gone
+            "#,
+        )
+        .unwrap();
+        std::fs::write(repo.join("otel/registry.yaml"), "groups: []\n").unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(["-c", "commit.gpgsign=false", "-c", "tag.gpgsign=false"])
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        git(&["add", "-A"]);
+        git(&["commit", "-qm", "first"]);
+        git(&["tag", "v1"]);
+
+        let acme = ModelSource::parse(&format!("{}@v1", repo.join("acme").display())).unwrap();
+        let mut sources = vec![("a".to_owned(), acme)];
+        let warnings = resolve_dependencies(&mut sources).unwrap();
+
+        assert_eq!(sources.len(), 2);
+        assert!(matches!(
+            &sources[1].1,
+            ModelSource::Git { dir, git_ref, .. } if dir.ends_with("otel") && git_ref == "v1"
+        ));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Skipping dependency gone of acme"));
+    }
+}
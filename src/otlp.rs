@@ -0,0 +1,846 @@
+use std::fmt::Display;
+
+use opentelemetry_proto::tonic::{
+    self as proto,
+    collector::{
+        logs::v1::ExportLogsServiceRequest, metrics::v1::ExportMetricsServiceRequest,
+        trace::v1::ExportTraceServiceRequest,
+    },
+};
+use prost::Message;
+
+/// A JSON value that remembers the line each object starts on, so problems
+/// can be reported against the original file.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object {
+        line: usize,
+        fields: Vec<(String, Json)>,
+    },
+}
+
+impl Json {
+    /// A field of an object, by its OTLP/JSON camelCase name or the
+    /// protobuf snake_case name.
+    fn get(&self, name: &str) -> Option<&Json> {
+        let Json::Object { fields, .. } = self else {
+            return None;
+        };
+        fields
+            .iter()
+            .find(|(field, _)| normalize(field) == normalize(name))
+            .map(|(_, value)| value)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+fn normalize(field: &str) -> String {
+    field.replace('_', "").to_lowercase()
+}
+
+/// How deeply values can nest, as in serde_json, so deep input can't
+/// overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => anyhow::bail!("line {}: expected {expected} but found {c}", self.line),
+            None => anyhow::bail!("line {}: expected {expected} but the file ended", self.line),
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<Json> {
+        if self.depth == MAX_DEPTH {
+            anyhow::bail!("line {}: nested more than {MAX_DEPTH} deep", self.line);
+        }
+        self.depth += 1;
+        let value = self.nested_value();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_value(&mut self) -> anyhow::Result<Json> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => {
+                let line = self.line;
+                self.next();
+                let mut fields = vec![];
+                self.skip_whitespace();
+                if self.chars.peek() == Some(&'}') {
+                    self.next();
+                    return Ok(Json::Object { line, fields });
+                }
+                loop {
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.expect(':')?;
+                    fields.push((name, self.value()?));
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => continue,
+                        Some('}') => return Ok(Json::Object { line, fields }),
+                        _ => anyhow::bail!("line {}: expected , or }}", self.line),
+                    }
+                }
+            }
+            Some('[') => {
+                self.next();
+                let mut values = vec![];
+                self.skip_whitespace();
+                if self.chars.peek() == Some(&']') {
+                    self.next();
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => continue,
+                        Some(']') => return Ok(Json::Array(values)),
+                        _ => anyhow::bail!("line {}: expected , or ]", self.line),
+                    }
+                }
+            }
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(_) => {
+                let mut literal = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_alphanumeric() || matches!(c, '-' | '+' | '.')) {
+                        break;
+                    }
+                    literal.push(c);
+                    self.next();
+                }
+                match literal.as_str() {
+                    "null" => Ok(Json::Null),
+                    "true" => Ok(Json::Bool(true)),
+                    "false" => Ok(Json::Bool(false)),
+                    _ if is_number(&literal) => Ok(Json::Number(literal)),
+                    _ => anyhow::bail!("line {}: unexpected {literal:?}", self.line),
+                }
+            }
+            None => anyhow::bail!("line {}: expected a value but the file ended", self.line),
+        }
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                    Some('u') => {
+                        let mut code = self.hex()?;
+                        // a surrogate pair is two escapes
+                        if (0xd800..0xdc00).contains(&code) {
+                            let low = match (self.next(), self.next()) {
+                                (Some('\\'), Some('u')) => self.hex()?,
+                                _ => 0,
+                            };
+                            if !(0xdc00..0xe000).contains(&low) {
+                                anyhow::bail!("line {}: invalid surrogate pair", self.line);
+                            }
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        }
+                        match char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => anyhow::bail!("line {}: invalid surrogate pair", self.line),
+                        }
+                    }
+                    Some(c) => anyhow::bail!("line {}: invalid escape \\{c}", self.line),
+                    None => break,
+                },
+                Some(c) if c < ' ' => {
+                    anyhow::bail!("line {}: control character in a string", self.line)
+                }
+                Some(c) => s.push(c),
+                None => break,
+            }
+        }
+        anyhow::bail!("line {}: unterminated string", self.line)
+    }
+
+    fn hex(&mut self) -> anyhow::Result<u32> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("line {}: invalid escape \\u{digits}", self.line);
+        }
+        Ok(u32::from_str_radix(&digits, 16)?)
+    }
+}
+
+/// Whether a literal is a number in JSON's grammar, which unlike Rust's
+/// doesn't allow `NaN`, `inf`, a leading `+` or `.`, or leading zeros.
+fn is_number(literal: &str) -> bool {
+    let bytes = literal.as_bytes();
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        *i > start
+    };
+    if bytes.get(i) == Some(&b'-') {
+        i += 1;
+    }
+    match bytes.get(i) {
+        Some(b'0') => i += 1,
+        Some(b'1'..=b'9') => {
+            digits(&mut i);
+        }
+        _ => return false,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        if !digits(&mut i) {
+            return false;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if !digits(&mut i) {
+            return false;
+        }
+    }
+    i == bytes.len()
+}
+
+/// Parse one JSON document, or several one per line as written by the
+/// collector's file exporter.
+fn parse(text: &str) -> anyhow::Result<Vec<Json>> {
+    let mut parser = Parser {
+        chars: text.chars().peekable(),
+        line: 1,
+        depth: 0,
+    };
+    let mut documents = vec![];
+    loop {
+        parser.skip_whitespace();
+        if parser.chars.peek().is_none() {
+            return Ok(documents);
+        }
+        documents.push(parser.value()?);
+    }
+}
+
+/// An OTLP attribute value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyValue {
+    String(String),
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    Bytes,
+    Array(Vec<AnyValue>),
+    KvList,
+    Empty,
+}
+
+impl AnyValue {
+    fn from_json(json: &Json) -> Self {
+        let Json::Object { fields, .. } = json else {
+            return AnyValue::Empty;
+        };
+        let Some((field, value)) = fields.first() else {
+            return AnyValue::Empty;
+        };
+        // 64 bit integers are strings in OTLP/JSON, but accept numbers too
+        let number = |value: &Json| match value {
+            Json::Number(n) | Json::String(n) => Some(n.clone()),
+            _ => None,
+        };
+        match (normalize(field).as_str(), value) {
+            ("stringvalue", Json::String(s)) => AnyValue::String(s.clone()),
+            ("boolvalue", Json::Bool(b)) => AnyValue::Bool(*b),
+            ("intvalue", value) => number(value)
+                .and_then(|n| n.parse().ok())
+                .map_or(AnyValue::Empty, AnyValue::Int),
+            ("doublevalue", value) => number(value)
+                .and_then(|n| n.parse().ok())
+                .map_or(AnyValue::Empty, AnyValue::Double),
+            ("bytesvalue", _) => AnyValue::Bytes,
+            ("arrayvalue", array) => match array.get("values") {
+                Some(Json::Array(values)) => {
+                    AnyValue::Array(values.iter().map(AnyValue::from_json).collect())
+                }
+                _ => AnyValue::Array(vec![]),
+            },
+            ("kvlistvalue", _) => AnyValue::KvList,
+            _ => AnyValue::Empty,
+        }
+    }
+
+    /// The semantic convention name of the value's type, e.g. `string[]`.
+    pub fn type_name(&self) -> String {
+        match self {
+            AnyValue::String(_) => "string".to_owned(),
+            AnyValue::Bool(_) => "boolean".to_owned(),
+            AnyValue::Int(_) => "int".to_owned(),
+            AnyValue::Double(_) => "double".to_owned(),
+            AnyValue::Bytes => "bytes".to_owned(),
+            AnyValue::Array(values) => match values.first() {
+                Some(value) => format!("{}[]", value.type_name()),
+                None => "[]".to_owned(),
+            },
+            AnyValue::KvList => "map".to_owned(),
+            AnyValue::Empty => "empty".to_owned(),
+        }
+    }
+}
+
+impl Display for AnyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnyValue::String(s) => write!(f, "{s:?}"),
+            AnyValue::Bool(b) => write!(f, "{b}"),
+            AnyValue::Int(i) => write!(f, "{i}"),
+            AnyValue::Double(d) => write!(f, "{d}"),
+            AnyValue::Array(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            AnyValue::Bytes | AnyValue::KvList | AnyValue::Empty => {
+                write!(f, "{}", self.type_name())
+            }
+        }
+    }
+}
+
+/// An attribute read from OTLP telemetry, with where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpAttribute {
+    pub key: String,
+    pub value: AnyValue,
+    /// The line the attribute starts on in OTLP/JSON, or 0 from protobuf.
+    pub line: usize,
+    /// Where the attribute was set, e.g. `resource` or `span GET /users > event exception`.
+    pub context: String,
+    /// The `service.name` of the resource the attribute belongs to.
+    pub service_name: Option<String>,
+}
+
+/// The label for an item of an OTLP list, e.g. `span GET /users`.
+fn label(field: &str, item: &Json) -> Option<String> {
+    let kind = match normalize(field).as_str() {
+        "resource" => return Some("resource".to_owned()),
+        "scope" => "scope",
+        "spans" => "span",
+        "events" => "event",
+        "links" => return Some("link".to_owned()),
+        "metrics" => "metric",
+        "datapoints" => return Some("data point".to_owned()),
+        "logrecords" => return Some("log record".to_owned()),
+        _ => return None,
+    };
+    Some(named(
+        kind,
+        item.get("name").and_then(Json::as_str).unwrap_or_default(),
+    ))
+}
+
+fn named(kind: &str, name: &str) -> String {
+    if name.is_empty() {
+        kind.to_owned()
+    } else {
+        format!("{kind} {name}")
+    }
+}
+
+fn service_name(resource: &Json) -> Option<String> {
+    let Some(Json::Array(attributes)) = resource.get("attributes") else {
+        return None;
+    };
+    attributes
+        .iter()
+        .find(|kv| kv.get("key").and_then(Json::as_str) == Some("service.name"))
+        .and_then(|kv| kv.get("value"))
+        .and_then(|value| match AnyValue::from_json(value) {
+            AnyValue::String(s) => Some(s),
+            _ => None,
+        })
+}
+
+fn walk(
+    json: &Json,
+    context: &[String],
+    service: &Option<String>,
+    attributes: &mut Vec<OtlpAttribute>,
+) {
+    match json {
+        Json::Object { fields, .. } => {
+            let service = json
+                .get("resource")
+                .and_then(service_name)
+                .or_else(|| service.clone());
+            for (field, value) in fields {
+                if field == "attributes" {
+                    let Json::Array(key_values) = value else {
+                        continue;
+                    };
+                    for kv in key_values {
+                        let (Json::Object { line, .. }, Some(key)) =
+                            (kv, kv.get("key").and_then(Json::as_str))
+                        else {
+                            continue;
+                        };
+                        attributes.push(OtlpAttribute {
+                            key: key.to_owned(),
+                            value: kv.get("value").map_or(AnyValue::Empty, AnyValue::from_json),
+                            line: *line,
+                            context: context.join(" > "),
+                            service_name: service.clone(),
+                        });
+                    }
+                    continue;
+                }
+                let items = match value {
+                    Json::Array(items) => items.iter().collect(),
+                    value => vec![value],
+                };
+                for item in items {
+                    match label(field, item) {
+                        Some(label) => {
+                            let mut context = context.to_vec();
+                            context.push(label);
+                            walk(item, &context, &service, attributes);
+                        }
+                        None => walk(item, context, &service, attributes),
+                    }
+                }
+            }
+        }
+        Json::Array(items) => {
+            for item in items {
+                walk(item, context, service, attributes);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read every resource, scope, span, event, link, metric data point and log
+/// record attribute from OTLP/JSON traces, metrics or logs.
+pub fn read_json(text: &str) -> anyhow::Result<Vec<OtlpAttribute>> {
+    let mut attributes = vec![];
+    for document in parse(text)? {
+        walk(&document, &[], &None, &mut attributes);
+    }
+    Ok(attributes)
+}
+
+/// An OTLP signal, as sent to its OTLP/HTTP path, e.g. `/v1/traces`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Traces,
+    Metrics,
+    Logs,
+}
+
+impl From<&proto::common::v1::AnyValue> for AnyValue {
+    fn from(value: &proto::common::v1::AnyValue) -> Self {
+        use proto::common::v1::any_value::Value;
+        match &value.value {
+            Some(Value::StringValue(s)) => AnyValue::String(s.clone()),
+            Some(Value::BoolValue(b)) => AnyValue::Bool(*b),
+            Some(Value::IntValue(i)) => AnyValue::Int(*i),
+            Some(Value::DoubleValue(d)) => AnyValue::Double(*d),
+            Some(Value::BytesValue(_)) => AnyValue::Bytes,
+            Some(Value::ArrayValue(array)) => {
+                AnyValue::Array(array.values.iter().map(AnyValue::from).collect())
+            }
+            Some(Value::KvlistValue(_)) => AnyValue::KvList,
+            None => AnyValue::Empty,
+        }
+    }
+}
+
+/// Collects the attributes of protobuf OTLP messages, labelled the same way
+/// as those read from OTLP/JSON.
+#[derive(Default)]
+struct Collector {
+    attributes: Vec<OtlpAttribute>,
+}
+
+impl Collector {
+    fn push(
+        &mut self,
+        key_values: &[proto::common::v1::KeyValue],
+        context: &[&str],
+        service: &Option<String>,
+    ) {
+        for kv in key_values {
+            self.attributes.push(OtlpAttribute {
+                key: kv.key.clone(),
+                value: kv.value.as_ref().map_or(AnyValue::Empty, AnyValue::from),
+                line: 0,
+                context: context.join(" > "),
+                service_name: service.clone(),
+            });
+        }
+    }
+
+    /// Push the resource's attributes and return its `service.name`.
+    fn resource(&mut self, resource: &Option<proto::resource::v1::Resource>) -> Option<String> {
+        let resource = resource.as_ref()?;
+        let service = resource
+            .attributes
+            .iter()
+            .find(|kv| kv.key == "service.name")
+            .and_then(|kv| kv.value.as_ref())
+            .and_then(|value| match AnyValue::from(value) {
+                AnyValue::String(s) => Some(s),
+                _ => None,
+            });
+        self.push(&resource.attributes, &["resource"], &service);
+        service
+    }
+
+    fn scope(
+        &mut self,
+        scope: &Option<proto::common::v1::InstrumentationScope>,
+        service: &Option<String>,
+    ) {
+        if let Some(scope) = scope {
+            self.push(&scope.attributes, &[&named("scope", &scope.name)], service);
+        }
+    }
+
+    fn traces(&mut self, request: &ExportTraceServiceRequest) {
+        for resource_spans in &request.resource_spans {
+            let service = self.resource(&resource_spans.resource);
+            for scope_spans in &resource_spans.scope_spans {
+                self.scope(&scope_spans.scope, &service);
+                for span in &scope_spans.spans {
+                    let label = named("span", &span.name);
+                    self.push(&span.attributes, &[&label], &service);
+                    for event in &span.events {
+                        let event_label = named("event", &event.name);
+                        self.push(&event.attributes, &[&label, &event_label], &service);
+                    }
+                    for link in &span.links {
+                        self.push(&link.attributes, &[&label, "link"], &service);
+                    }
+                }
+            }
+        }
+    }
+
+    fn metrics(&mut self, request: &ExportMetricsServiceRequest) {
+        use proto::metrics::v1::metric::Data;
+        for resource_metrics in &request.resource_metrics {
+            let service = self.resource(&resource_metrics.resource);
+            for scope_metrics in &resource_metrics.scope_metrics {
+                self.scope(&scope_metrics.scope, &service);
+                for metric in &scope_metrics.metrics {
+                    let data_points: Vec<&[proto::common::v1::KeyValue]> = match &metric.data {
+                        Some(Data::Gauge(gauge)) => gauge
+                            .data_points
+                            .iter()
+                            .map(|p| &p.attributes[..])
+                            .collect(),
+                        Some(Data::Sum(sum)) => {
+                            sum.data_points.iter().map(|p| &p.attributes[..]).collect()
+                        }
+                        Some(Data::Histogram(histogram)) => histogram
+                            .data_points
+                            .iter()
+                            .map(|p| &p.attributes[..])
+                            .collect(),
+                        Some(Data::ExponentialHistogram(histogram)) => histogram
+                            .data_points
+                            .iter()
+                            .map(|p| &p.attributes[..])
+                            .collect(),
+                        Some(Data::Summary(summary)) => summary
+                            .data_points
+                            .iter()
+                            .map(|p| &p.attributes[..])
+                            .collect(),
+                        None => vec![],
+                    };
+                    let label = named("metric", &metric.name);
+                    for attributes in data_points {
+                        self.push(attributes, &[&label, "data point"], &service);
+                    }
+                }
+            }
+        }
+    }
+
+    fn logs(&mut self, request: &ExportLogsServiceRequest) {
+        for resource_logs in &request.resource_logs {
+            let service = self.resource(&resource_logs.resource);
+            for scope_logs in &resource_logs.scope_logs {
+                self.scope(&scope_logs.scope, &service);
+                for log_record in &scope_logs.log_records {
+                    self.push(&log_record.attributes, &["log record"], &service);
+                }
+            }
+        }
+    }
+}
+
+/// Read every resource, scope, span, event, link, metric data point and log
+/// record attribute from a protobuf OTLP export request.
+pub fn read_protobuf(signal: Signal, bytes: &[u8]) -> anyhow::Result<Vec<OtlpAttribute>> {
+    let mut collector = Collector::default();
+    match signal {
+        Signal::Traces => collector.traces(&ExportTraceServiceRequest::decode(bytes)?),
+        Signal::Metrics => collector.metrics(&ExportMetricsServiceRequest::decode(bytes)?),
+        Signal::Logs => collector.logs(&ExportLogsServiceRequest::decode(bytes)?),
+    }
+    Ok(collector.attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json =
+            parse("{\"a\": [1, -2.5e3, true, null],\n \"b\": {\"c\": \"\\u00e9\\\"\"}}").unwrap();
+        assert_eq!(
+            json,
+            vec![Json::Object {
+                line: 1,
+                fields: vec![
+                    (
+                        "a".to_owned(),
+                        Json::Array(vec![
+                            Json::Number("1".to_owned()),
+                            Json::Number("-2.5e3".to_owned()),
+                            Json::Bool(true),
+                            Json::Null,
+                        ])
+                    ),
+                    (
+                        "b".to_owned(),
+                        Json::Object {
+                            line: 2,
+                            fields: vec![("c".to_owned(), Json::String("é\"".to_owned()))]
+                        }
+                    ),
+                ]
+            }]
+        );
+        assert_eq!(
+            parse("{\n\"a\": tru}").unwrap_err().to_string(),
+            "line 2: unexpected \"tru\""
+        );
+        assert!(parse(&format!("{}{}", "[".repeat(128), "]".repeat(128))).is_ok());
+        assert_eq!(
+            parse(&"[".repeat(100_000)).unwrap_err().to_string(),
+            "line 1: nested more than 128 deep"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_json() {
+        for valid in [
+            "0",
+            "-0.5",
+            "1e10",
+            "2.5E-3",
+            "\"\\/\\ud83d\\ude00\"",
+            "\t[1]\r\n",
+        ] {
+            assert!(parse(valid).is_ok(), "{valid}");
+        }
+        for invalid in [
+            "NaN",
+            "inf",
+            "+1",
+            "01",
+            "1.",
+            ".5",
+            "1e",
+            "0x1",
+            "\"a\tb\"",
+            "\"\\x\"",
+            "\"\\u+123\"",
+            "\"\\ud800\"",
+            "\"\\udc00\"",
+            "\u{a0}1",
+        ] {
+            assert!(parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_read_json() {
+        let traces = r#"{"resourceSpans": [{
+  "resource": {"attributes": [
+    {"key": "service.name", "value": {"stringValue": "checkout"}}
+  ]},
+  "scopeSpans": [{"spans": [{
+    "name": "GET /cart",
+    "attributes": [
+      {"key": "http.response.status_code", "value": {"intValue": "200"}},
+      {"key": "http.request.header.accept", "value": {"arrayValue": {"values": [{"stringValue": "*/*"}]}}}
+    ],
+    "events": [{"name": "exception", "attributes": [
+      {"key": "exception.type", "value": {"stringValue": "Timeout"}}
+    ]}]
+  }]}]
+}]}
+{"resourceLogs": [{"scopeLogs": [{"logRecords": [{"attributes": [{"key": "log.iostream", "value": {"stringValue": "stdout"}}]}]}]}]}
+"#;
+        let attributes = read_json(traces).unwrap();
+        let summary: Vec<(&str, usize, &str, Option<&str>)> = attributes
+            .iter()
+            .map(|a| {
+                (
+                    a.key.as_str(),
+                    a.line,
+                    a.context.as_str(),
+                    a.service_name.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("service.name", 3, "resource", Some("checkout")),
+                (
+                    "http.response.status_code",
+                    8,
+                    "span GET /cart",
+                    Some("checkout")
+                ),
+                (
+                    "http.request.header.accept",
+                    9,
+                    "span GET /cart",
+                    Some("checkout")
+                ),
+                (
+                    "exception.type",
+                    12,
+                    "span GET /cart > event exception",
+                    Some("checkout")
+                ),
+                ("log.iostream", 16, "log record", None),
+            ]
+        );
+        assert_eq!(attributes[1].value, AnyValue::Int(200));
+        assert_eq!(attributes[2].value.type_name(), "string[]");
+    }
+
+    #[test]
+    fn test_read_protobuf() {
+        use proto::{
+            common::v1::{any_value::Value, AnyValue as ProtoValue, KeyValue},
+            resource::v1::Resource,
+            trace::v1::{span::Event, ResourceSpans, ScopeSpans, Span},
+        };
+        let kv = |key: &str, value: Value| KeyValue {
+            key: key.to_owned(),
+            value: Some(ProtoValue { value: Some(value) }),
+        };
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: Some(Resource {
+                    attributes: vec![kv(
+                        "service.name",
+                        Value::StringValue("checkout".to_owned()),
+                    )],
+                    ..Default::default()
+                }),
+                scope_spans: vec![ScopeSpans {
+                    spans: vec![Span {
+                        name: "GET /cart".to_owned(),
+                        attributes: vec![kv("http.response.status_code", Value::IntValue(200))],
+                        events: vec![Event {
+                            name: "exception".to_owned(),
+                            attributes: vec![kv("exception.escaped", Value::BoolValue(true))],
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        let attributes = read_protobuf(Signal::Traces, &request.encode_to_vec()).unwrap();
+        let summary: Vec<(&str, &str, &AnyValue)> = attributes
+            .iter()
+            .map(|a| (a.key.as_str(), a.context.as_str(), &a.value))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "service.name",
+                    "resource",
+                    &AnyValue::String("checkout".to_owned())
+                ),
+                (
+                    "http.response.status_code",
+                    "span GET /cart",
+                    &AnyValue::Int(200)
+                ),
+                (
+                    "exception.escaped",
+                    "span GET /cart > event exception",
+                    &AnyValue::Bool(true)
+                ),
+            ]
+        );
+        assert!(attributes
+            .iter()
+            .all(|a| a.service_name.as_deref() == Some("checkout")));
+        assert!(read_protobuf(Signal::Logs, b"not protobuf").is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use honeycomb_client::honeycomb::HoneyComb;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+/// The API of the US region, used unless `HONEYCOMB_API_ENDPOINT` names
+/// another, e.g. `https://api.eu1.honeycomb.io` for EU keys.
+const DEFAULT_ENDPOINT: &str = "https://api.honeycomb.io";
+
+/// How long to wait between polls for a query result, and how many polls to
+/// make before giving up.
+const POLL: Duration = Duration::from_millis(500);
+const MAX_POLLS: usize = 60;
+
+/// Parse a time window such as `30m`, `2h` or `7d` into seconds.
+pub fn parse_window(window: &str) -> Option<u64> {
+    let (number, unit) = window.split_at(window.char_indices().last()?.0);
+    let seconds = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    number
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(seconds)
+        .filter(|seconds| *seconds > 0)
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct QueryResult {
+    complete: bool,
+    data: Option<QueryData>,
+}
+
+#[derive(Deserialize)]
+struct QueryData {
+    results: Vec<QueryRow>,
+}
+
+#[derive(Deserialize)]
+struct QueryRow {
+    data: serde_json::Map<String, Value>,
+}
+
+/// Runs Honeycomb queries, sharing one HTTP client between them.
+#[derive(Clone)]
+pub struct Queries {
+    client: reqwest::Client,
+    api_key: String,
+    url: String,
+}
+
+impl Queries {
+    pub fn new(hc: &HoneyComb) -> Self {
+        let endpoint =
+            std::env::var("HONEYCOMB_API_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.to_owned());
+        Queries {
+            client: reqwest::Client::new(),
+            api_key: hc.api_key.clone(),
+            url: format!("{}/1/", endpoint.trim_end_matches('/')),
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<T> {
+        Ok(request
+            .header("X-Honeycomb-Team", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Count the events with each value of a column in a dataset over the
+    /// last `time_range` seconds, most frequent first.
+    pub async fn value_counts(
+        &self,
+        dataset: &str,
+        column: &str,
+        time_range: u64,
+    ) -> anyhow::Result<Vec<(String, u64)>> {
+        let url = &self.url;
+        let query: Created = self
+            .send(
+                self.client
+                    .post(format!("{url}queries/{dataset}"))
+                    .json(&json!({
+                        "breakdowns": [column],
+                        "calculations": [{"op": "COUNT"}],
+                        "orders": [{"op": "COUNT", "order": "descending"}],
+                        "time_range": time_range,
+                        "limit": 1000,
+                    })),
+            )
+            .await?;
+        let result: Created = self
+            .send(
+                self.client
+                    .post(format!("{url}query_results/{dataset}"))
+                    .json(&json!({"query_id": query.id, "disable_series": true})),
+            )
+            .await?;
+
+        for _ in 0..MAX_POLLS {
+            let result: QueryResult = self
+                .send(
+                    self.client
+                        .get(format!("{url}query_results/{dataset}/{}", result.id)),
+                )
+                .await?;
+            if !result.complete {
+                tokio::time::sleep(POLL).await;
+                continue;
+            }
+            let rows = result.data.context("query result has no data")?.results;
+            return Ok(rows
+                .into_iter()
+                .filter_map(|row| {
+                    // events without the column are grouped under null
+                    let value = match row.data.get(column)? {
+                        Value::Null => return None,
+                        Value::String(s) => s.clone(),
+                        value => value.to_string(),
+                    };
+                    Some((value, row.data.get("COUNT")?.as_u64()?))
+                })
+                .collect());
+        }
+        anyhow::bail!("timed out waiting for the query result")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("30m"), Some(1800));
+        assert_eq!(parse_window("2h"), Some(7200));
+        assert_eq!(parse_window("7d"), Some(604800));
+        assert_eq!(parse_window("0h"), None);
+        assert_eq!(parse_window("2w"), None);
+        assert_eq!(parse_window("h"), None);
+        assert_eq!(parse_window(""), None);
+        assert_eq!(parse_window("2é"), None);
+    }
+}
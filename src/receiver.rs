@@ -0,0 +1,288 @@
+use std::{collections::BTreeMap, io::Read, sync::Arc};
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use flate2::read::GzDecoder;
+use tokio::sync::mpsc;
+
+use crate::{
+    otlp::{self, AnyValue, OtlpAttribute, Signal},
+    snapshot::{Column, Snapshot},
+    watch::Reload,
+    AppState,
+};
+
+/// The dataset for telemetry without a `service.name`, as Honeycomb names it.
+const UNKNOWN_SERVICE: &str = "unknown_service";
+
+#[derive(Clone)]
+struct Receiver {
+    state: AppState,
+    reload: mpsc::UnboundedSender<Reload>,
+}
+
+/// The Honeycomb column type an attribute value would be stored as.
+fn column_type(value: &AnyValue) -> &'static str {
+    match value {
+        AnyValue::Int(_) => "integer",
+        AnyValue::Double(_) => "float",
+        AnyValue::Bool(_) => "boolean",
+        _ => "string",
+    }
+}
+
+/// Add a type to a column, or the column to the list, unless it's there.
+fn add_type(columns: &mut Vec<Column>, key_name: &str, r#type: &str) {
+    match columns.iter_mut().find(|c| c.key_name == key_name) {
+        Some(column) if column.types().any(|t| t == r#type) => {}
+        Some(column) => column.other_types.push(r#type.to_owned()),
+        None => columns.push(Column::new(key_name, r#type)),
+    }
+}
+
+/// The columns each service's attributes would make, by `service.name`. A
+/// key sent with values of several types keeps them all, the first as its
+/// type and the rest as its other types.
+fn columns(attributes: &[OtlpAttribute]) -> BTreeMap<String, Vec<Column>> {
+    let mut columns: BTreeMap<String, Vec<Column>> = BTreeMap::new();
+    for attribute in attributes {
+        let service = attribute.service_name.as_deref().unwrap_or(UNKNOWN_SERVICE);
+        let service_columns = columns.entry(service.to_owned()).or_default();
+        add_type(
+            service_columns,
+            &attribute.key,
+            column_type(&attribute.value),
+        );
+    }
+    columns
+}
+
+/// Add the columns, and the types of known columns, that the snapshot
+/// doesn't have yet and return how many. Only the datasets that change are
+/// copied, and the snapshot itself only if it's shared.
+fn merge(snapshot: &mut Arc<Snapshot>, columns: BTreeMap<String, Vec<Column>>) -> usize {
+    let mut added = 0;
+    for (service, service_columns) in columns {
+        for column in &service_columns {
+            for r#type in column.types() {
+                let known = snapshot
+                    .datasets
+                    .get(&service)
+                    .and_then(|dataset| dataset.iter().find(|c| c.key_name == column.key_name));
+                if known.is_some_and(|known| known.types().any(|t| t == r#type)) {
+                    continue;
+                }
+                let datasets = &mut Arc::make_mut(snapshot).datasets;
+                let dataset = Arc::make_mut(datasets.entry(service.clone()).or_default());
+                add_type(dataset, &column.key_name, r#type);
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+/// The largest request, before and after gunzipping. Exporters batch up to
+/// several MB per request.
+const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// A gzipped body that unzips to more than [`MAX_BODY`].
+#[derive(Debug)]
+struct TooLarge;
+
+impl std::fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the body unzips to more than {} MiB",
+            MAX_BODY / 1024 / 1024
+        )
+    }
+}
+
+impl std::error::Error for TooLarge {}
+
+fn read(signal: Signal, gzip: bool, json: bool, body: &[u8]) -> anyhow::Result<Vec<OtlpAttribute>> {
+    let mut unzipped = vec![];
+    let body = if gzip {
+        GzDecoder::new(body)
+            .take(MAX_BODY as u64 + 1)
+            .read_to_end(&mut unzipped)?;
+        if unzipped.len() > MAX_BODY {
+            return Err(TooLarge.into());
+        }
+        &unzipped[..]
+    } else {
+        body
+    };
+    if json {
+        otlp::read_json(std::str::from_utf8(body)?)
+    } else {
+        otlp::read_protobuf(signal, body)
+    }
+}
+
+async fn receive(signal: Signal, receiver: Receiver, headers: HeaderMap, body: Bytes) -> Response {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    let gzip = header(header::CONTENT_ENCODING) == "gzip";
+    let json = header(header::CONTENT_TYPE).starts_with("application/json");
+
+    // unzipping and parsing a large batch would hold up the runtime
+    let attributes =
+        match tokio::task::spawn_blocking(move || read(signal, gzip, json, &body)).await {
+            Ok(Ok(attributes)) => attributes,
+            Ok(Err(e)) if e.is::<TooLarge>() => {
+                return (StatusCode::PAYLOAD_TOO_LARGE, e.to_string()).into_response()
+            }
+            Ok(Err(e)) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+
+    let columns = columns(&attributes);
+    let added = merge(&mut receiver.state.snapshot.write().unwrap(), columns);
+    if added > 0 {
+        println!("received {added} new columns or column types");
+        let _ = receiver.reload.send(Reload::Columns);
+    }
+
+    // an empty export response, in the encoding of the request
+    if json {
+        ([(header::CONTENT_TYPE, "application/json")], "{}").into_response()
+    } else {
+        ([(header::CONTENT_TYPE, "application/x-protobuf")], "").into_response()
+    }
+}
+
+async fn traces_handler(
+    State(receiver): State<Receiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    receive(Signal::Traces, receiver, headers, body).await
+}
+
+async fn metrics_handler(
+    State(receiver): State<Receiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    receive(Signal::Metrics, receiver, headers, body).await
+}
+
+async fn logs_handler(
+    State(receiver): State<Receiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    receive(Signal::Logs, receiver, headers, body).await
+}
+
+/// Receive OTLP/HTTP traces, metrics and logs, as JSON or protobuf, on
+/// `addr`. Each `service.name` becomes a dataset whose columns are the
+/// attribute keys it sends, and the model is reloaded when a new one arrives.
+pub async fn serve(
+    addr: &str,
+    state: AppState,
+    reload: mpsc::UnboundedSender<Reload>,
+) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/v1/traces", post(traces_handler))
+        .route("/v1/metrics", post(metrics_handler))
+        .route("/v1/logs", post(logs_handler))
+        .layer(DefaultBodyLimit::max(MAX_BODY))
+        .with_state(Receiver { state, reload });
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    println!("receiving OTLP on {}", listener.local_addr()?);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("OTLP receiver stopped: {e}");
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns() {
+        let logs = r#"{"resourceLogs": [
+  {"resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "checkout"}}]},
+   "scopeLogs": [{"logRecords": [
+     {"attributes": [{"key": "http.response.status_code", "value": {"intValue": "200"}}]},
+     {"attributes": [{"key": "http.response.status_code", "value": {"stringValue": "200"}}]}
+   ]}]},
+  {"scopeLogs": [{"logRecords": [
+     {"attributes": [{"key": "retry", "value": {"boolValue": true}}]}
+   ]}]}
+]}"#;
+        let columns = columns(&otlp::read_json(logs).unwrap());
+        let column = Column::new;
+        let mut status_code = column("http.response.status_code", "integer");
+        status_code.other_types.push("string".to_owned());
+        assert_eq!(
+            columns,
+            BTreeMap::from([
+                (
+                    "checkout".to_owned(),
+                    vec![column("service.name", "string"), status_code.clone(),]
+                ),
+                (
+                    "unknown_service".to_owned(),
+                    vec![column("retry", "boolean")]
+                ),
+            ])
+        );
+
+        // a key already seen with one type gains the other
+        let mut snapshot = Snapshot::default();
+        snapshot.datasets.insert(
+            "checkout".to_owned(),
+            Arc::new(vec![
+                column("service.name", "string"),
+                column("http.response.status_code", "integer"),
+            ]),
+        );
+        let mut snapshot = Arc::new(snapshot);
+        let shared = snapshot.clone();
+        assert_eq!(merge(&mut snapshot, columns.clone()), 2);
+        assert_eq!(merge(&mut snapshot, columns), 0);
+        assert_eq!(snapshot.datasets["checkout"][1], status_code);
+        assert_eq!(snapshot.datasets["unknown_service"].len(), 1);
+        assert_eq!(shared.datasets["checkout"][1].other_types.len(), 0);
+    }
+
+    #[test]
+    fn test_read_gzip() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let gzip = |body: &[u8]| {
+            let mut encoder = GzEncoder::new(vec![], Compression::fast());
+            encoder.write_all(body).unwrap();
+            encoder.finish().unwrap()
+        };
+        let logs = r#"{"resourceLogs": [{"scopeLogs": [{"logRecords": [
+  {"attributes": [{"key": "retry", "value": {"boolValue": true}}]}
+]}]}]}"#;
+        let attributes = read(Signal::Logs, true, true, &gzip(logs.as_bytes())).unwrap();
+        assert_eq!(attributes.len(), 1);
+
+        let bomb = gzip(&vec![b' '; MAX_BODY + 1]);
+        assert!(read(Signal::Logs, true, true, &bomb)
+            .unwrap_err()
+            .is::<TooLarge>());
+    }
+}
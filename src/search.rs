@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::{
+    data::parent_path,
+    semconv::{Attribute, Examples, Type},
+};
+
+const MAX_RESULTS: usize = 50;
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Key,
+    Brief,
+    Member,
+    Example,
+    Note,
+}
+
+impl Field {
+    fn weight(&self) -> f64 {
+        match self {
+            Field::Key => 10.0,
+            Field::Brief => 4.0,
+            Field::Member => 3.0,
+            Field::Example => 2.0,
+            Field::Note => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Match {
+    Exact,
+    Prefix,
+    Fuzzy,
+}
+
+impl Match {
+    fn weight(&self) -> f64 {
+        match self {
+            Match::Exact => 1.0,
+            Match::Prefix => 0.6,
+            Match::Fuzzy => 0.3,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Document {
+    key: String,
+    registry_name: String,
+    deprecated: bool,
+    fields: Vec<(Field, String)>,
+}
+
+/// A ranked attribute. The API returns the plain text; the highlighted HTML
+/// is only for the search template.
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub key: String,
+    pub parent: String,
+    pub registry_name: String,
+    pub snippet: String,
+    pub score: f64,
+    #[serde(skip)]
+    pub key_html: String,
+    #[serde(skip)]
+    pub snippet_html: String,
+}
+
+/// An in-memory inverted index over every attribute's key, brief, note,
+/// enum members and examples.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    documents: Vec<Document>,
+    terms: BTreeMap<String, Vec<(usize, Field)>>,
+}
+
+impl SearchIndex {
+    pub fn new(attribute_map: &HashMap<String, Attribute>) -> Self {
+        let mut index = SearchIndex::default();
+        let mut keys: Vec<_> = attribute_map.keys().collect();
+        keys.sort();
+        for key in keys {
+            index.add(key, &attribute_map[key]);
+        }
+        index
+    }
+
+    fn add(&mut self, key: &str, attribute: &Attribute) {
+        let mut fields = vec![(Field::Key, key.to_owned())];
+        if let Some(brief) = &attribute.brief {
+            fields.push((Field::Brief, brief.clone()));
+        }
+        if let Some(note) = &attribute.note {
+            fields.push((Field::Note, note.clone()));
+        }
+        if let Some(Type::Complex(complex)) = &attribute.r#type {
+            for member in &complex.members {
+                fields.push((Field::Member, member.value.to_string()));
+                if let Some(brief) = &member.brief {
+                    fields.push((Field::Member, brief.clone()));
+                }
+            }
+        }
+        match &attribute.examples {
+            Some(Examples::SimpleType(example)) => {
+                fields.push((Field::Example, example.to_string()));
+            }
+            Some(Examples::ArrayType(examples)) => {
+                for example in examples {
+                    fields.push((Field::Example, example.to_string()));
+                }
+            }
+            None => {}
+        }
+
+        let doc_id = self.documents.len();
+        for (field, text) in &fields {
+            for term in tokenize(text) {
+                let postings = self.terms.entry(term).or_default();
+                if !postings.contains(&(doc_id, *field)) {
+                    postings.push((doc_id, *field));
+                }
+            }
+        }
+        self.documents.push(Document {
+            key: key.to_owned(),
+            registry_name: attribute.registry_name.clone().unwrap_or_default(),
+            deprecated: attribute.deprecated.is_some(),
+            fields,
+        });
+    }
+
+    /// Find the index terms matching a single query term, exactly, by prefix
+    /// or within a small edit distance.
+    fn matching_terms(&self, query_term: &str) -> Vec<(&str, Match)> {
+        let mut matches = vec![];
+        for (term, _) in self.terms.range(query_term.to_owned()..) {
+            if !term.starts_with(query_term) {
+                break;
+            }
+            let kind = if term == query_term {
+                Match::Exact
+            } else {
+                Match::Prefix
+            };
+            matches.push((term.as_str(), kind));
+        }
+        let max_distance = match query_term.chars().count() {
+            0..=3 => 0,
+            4..=7 => 1,
+            _ => 2,
+        };
+        if max_distance > 0 {
+            for term in self.terms.keys() {
+                if term.starts_with(query_term) {
+                    continue;
+                }
+                if levenshtein(query_term, term, max_distance).is_some() {
+                    matches.push((term.as_str(), Match::Fuzzy));
+                }
+            }
+        }
+        matches
+    }
+
+    /// Rank all attributes against the query. Every query term must match
+    /// somewhere in the attribute for it to be returned.
+    pub fn search(&self, query: &str) -> Vec<SearchResult> {
+        let query_terms = tokenize(query);
+        if query_terms.is_empty() {
+            return vec![];
+        }
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        let mut matched_terms: HashMap<usize, Vec<String>> = HashMap::new();
+        for (i, query_term) in query_terms.iter().enumerate() {
+            let mut best: HashMap<usize, f64> = HashMap::new();
+            for (term, kind) in self.matching_terms(query_term) {
+                for (doc_id, field) in &self.terms[term] {
+                    let score = field.weight() * kind.weight();
+                    let entry = best.entry(*doc_id).or_insert(0.0);
+                    if score > *entry {
+                        *entry = score;
+                    }
+                    matched_terms
+                        .entry(*doc_id)
+                        .or_default()
+                        .push(term.to_owned());
+                }
+            }
+            if i == 0 {
+                scores = best;
+            } else {
+                scores = scores
+                    .into_iter()
+                    .filter_map(|(doc_id, score)| best.get(&doc_id).map(|s| (doc_id, score + s)))
+                    .collect();
+            }
+        }
+
+        let lowered_query = query.trim().to_lowercase();
+        let mut results: Vec<SearchResult> = scores
+            .into_iter()
+            .map(|(doc_id, mut score)| {
+                let doc = &self.documents[doc_id];
+                if doc.key == lowered_query {
+                    score += 100.0;
+                } else if doc.key.starts_with(&lowered_query) {
+                    score += 20.0;
+                }
+                if doc.deprecated {
+                    score *= 0.5;
+                }
+                let terms = &matched_terms[&doc_id];
+                let snippet = doc.snippet(terms);
+                SearchResult {
+                    key: doc.key.clone(),
+                    parent: parent_path(&doc.key),
+                    registry_name: doc.registry_name.clone(),
+                    key_html: highlight(&doc.key, terms),
+                    snippet_html: highlight(&snippet, terms),
+                    snippet,
+                    score,
+                }
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.key.cmp(&b.key)));
+        results.truncate(MAX_RESULTS);
+        results
+    }
+}
+
+impl Document {
+    /// Pick the first non-key field containing a matched term, falling back
+    /// to the brief, and cut it down to an excerpt.
+    fn snippet(&self, terms: &[String]) -> String {
+        let text = self
+            .fields
+            .iter()
+            .filter(|(field, _)| *field != Field::Key)
+            .find(|(_, text)| tokenize(text).iter().any(|t| terms.contains(t)))
+            .or_else(|| self.fields.iter().find(|(field, _)| *field == Field::Brief))
+            .map(|(_, text)| text.as_str())
+            .unwrap_or("");
+        excerpt(text, terms)
+    }
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+        .collect()
+}
+
+/// Cut a window of text around the first matched term.
+fn excerpt(text: &str, terms: &[String]) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SNIPPET_CHARS {
+        return text;
+    }
+    let lowered = text.to_lowercase();
+    let start = terms
+        .iter()
+        .filter_map(|t| lowered.find(t.as_str()))
+        .min()
+        .unwrap_or(0);
+    let start = lowered[..start]
+        .chars()
+        .count()
+        .saturating_sub(SNIPPET_CHARS / 4);
+    let window: String = text.chars().skip(start).take(SNIPPET_CHARS).collect();
+    let prefix = if start > 0 { "…" } else { "" };
+    format!("{prefix}{window}…")
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// HTML-escape the text, wrapping each word that matched a query term in `<mark>`.
+fn highlight(text: &str, terms: &[String]) -> String {
+    let mut html = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, html: &mut String| {
+        if word.is_empty() {
+            return;
+        }
+        if terms.contains(&word.to_lowercase()) {
+            html.push_str(&format!("<mark>{}</mark>", escape_html(word)));
+        } else {
+            html.push_str(&escape_html(word));
+        }
+        word.clear();
+    };
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut html);
+            html.push_str(&escape_html(&c.to_string()));
+        }
+    }
+    flush(&mut word, &mut html);
+    html
+}
+
+/// Edit distance between two strings, or None if it exceeds `max`.
+fn levenshtein(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|m| *m > max) {
+            return None;
+        }
+        prev = current;
+    }
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(yaml: &str) -> Attribute {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn index() -> SearchIndex {
+        let mut map = HashMap::new();
+        map.insert(
+            "http.request.method".to_owned(),
+            attribute(
+                "id: request.method\n\
+                 type: string\n\
+                 brief: HTTP request method.\n\
+                 examples: [GET, POST]",
+            ),
+        );
+        map.insert(
+            "http.response.status_code".to_owned(),
+            attribute(
+                "id: response.status_code\n\
+                 type: int\n\
+                 brief: The HTTP response status code.\n\
+                 note: Uses the method of the request <b>",
+            ),
+        );
+        map.insert(
+            "db.system".to_owned(),
+            attribute(
+                "id: system\n\
+                 type:\n  members:\n    - value: postgresql\n      brief: PostgreSQL\n\
+                 brief: The database management system.",
+            ),
+        );
+        SearchIndex::new(&map)
+    }
+
+    #[test]
+    fn test_exact_key_ranks_first() {
+        let results = index().search("http.request.method");
+        assert_eq!(results[0].key, "http.request.method");
+    }
+
+    #[test]
+    fn test_all_terms_must_match() {
+        let results = index().search("http status");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "http.response.status_code");
+    }
+
+    #[test]
+    fn test_prefix_and_member_match() {
+        let results = index().search("postgres");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "db.system");
+        assert!(results[0].snippet_html.contains("<mark>postgresql</mark>"));
+    }
+
+    #[test]
+    fn test_json_is_plain_text() {
+        let results = index().search("uses");
+        assert!(results[0].snippet_html.contains("&lt;b&gt;"));
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert_eq!(json["key"], "http.response.status_code");
+        assert_eq!(json["snippet"], "Uses the method of the request <b>");
+        assert!(json.get("key_html").is_none());
+        assert!(json.get("snippet_html").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        let results = index().search("databse");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "db.system");
+    }
+
+    #[test]
+    fn test_highlight_escapes_html() {
+        let terms = vec!["request".to_owned()];
+        assert_eq!(
+            highlight("the request <b>", &terms),
+            "the <mark>request</mark> &lt;b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("method", "methd", 1), Some(1));
+        assert_eq!(levenshtein("method", "mthd", 1), None);
+    }
+}
@@ -1,13 +1,16 @@
-use glob::glob;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 use std::{
     collections::{BTreeMap, HashMap},
     fmt::{Display, Formatter},
-    fs::File,
-    path::PathBuf,
+    io::Read,
 };
 
-#[derive(Debug, Deserialize, Clone)]
+use crate::{
+    manifest::{is_manifest, Manifest},
+    source::ModelSource,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum MemberValue {
     StringType(String),
@@ -23,13 +26,14 @@ impl Display for MemberValue {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Member {
+    pub id: Option<String>,
     pub value: MemberValue,
     pub brief: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ComplexType {
     #[serde(default)]
     pub allow_custom_values: bool,
@@ -59,7 +63,7 @@ impl Display for ComplexType {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum PrimitiveType {
     Any,
@@ -105,6 +109,12 @@ impl Display for PrimitiveType {
     }
 }
 
+impl Serialize for PrimitiveType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 fn deserialize_primitive_type<'de, D>(deserializer: D) -> Result<PrimitiveType, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -135,7 +145,7 @@ where
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Type {
     #[serde(deserialize_with = "deserialize_primitive_type")]
@@ -152,7 +162,7 @@ impl Display for Type {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum ValueType {
     String(String),
@@ -182,41 +192,304 @@ impl Display for ValueType {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Examples {
     ArrayType(Vec<ValueType>),
     SimpleType(ValueType),
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl Display for Examples {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Examples::SimpleType(example) => write!(f, "{example}"),
+            Examples::ArrayType(examples) => {
+                let examples: Vec<String> = examples.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", examples.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stability {
+    Stable,
+    ReleaseCandidate,
+    Beta,
+    Alpha,
+    Experimental,
+    Development,
+    Deprecated,
+}
+
+impl Stability {
+    pub fn is_stable(&self) -> bool {
+        matches!(self, Stability::Stable)
+    }
+}
+
+impl Display for Stability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stability::Stable => write!(f, "stable"),
+            Stability::ReleaseCandidate => write!(f, "release_candidate"),
+            Stability::Beta => write!(f, "beta"),
+            Stability::Alpha => write!(f, "alpha"),
+            Stability::Experimental => write!(f, "experimental"),
+            Stability::Development => write!(f, "development"),
+            Stability::Deprecated => write!(f, "deprecated"),
+        }
+    }
+}
+
+impl Serialize for Stability {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Stability {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: serde_yaml::Value = serde::Deserialize::deserialize(deserializer)?;
+        match s {
+            serde_yaml::Value::String(s) => match s.as_str() {
+                "stable" => Ok(Stability::Stable),
+                "release_candidate" => Ok(Stability::ReleaseCandidate),
+                "beta" => Ok(Stability::Beta),
+                "alpha" => Ok(Stability::Alpha),
+                "experimental" => Ok(Stability::Experimental),
+                "development" => Ok(Stability::Development),
+                "deprecated" => Ok(Stability::Deprecated),
+                _ => Err(serde::de::Error::custom(format!(
+                    "Failed to parse stability: {s}"
+                ))),
+            },
+            _ => Err(serde::de::Error::custom("Failed to parse stability")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequirementLevel {
+    Required,
+    Recommended(Option<String>),
+    OptIn,
+    ConditionallyRequired(String),
+}
+
+impl RequirementLevel {
+    pub fn condition(&self) -> Option<&str> {
+        match self {
+            RequirementLevel::Recommended(text) => text.as_deref(),
+            RequirementLevel::ConditionallyRequired(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RequirementLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequirementLevel::Required => write!(f, "required"),
+            RequirementLevel::Recommended(_) => write!(f, "recommended"),
+            RequirementLevel::OptIn => write!(f, "opt-in"),
+            RequirementLevel::ConditionallyRequired(_) => write!(f, "conditionally required"),
+        }
+    }
+}
+
+impl Serialize for RequirementLevel {
+    /// Mirror the YAML form: a plain scalar, or a single entry map when
+    /// there's condition text.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut condition = BTreeMap::new();
+        match self {
+            RequirementLevel::Required => return serializer.serialize_str("required"),
+            RequirementLevel::Recommended(None) => return serializer.serialize_str("recommended"),
+            RequirementLevel::OptIn => return serializer.serialize_str("opt_in"),
+            RequirementLevel::Recommended(Some(text)) => condition.insert("recommended", text),
+            RequirementLevel::ConditionallyRequired(text) => {
+                condition.insert("conditionally_required", text)
+            }
+        };
+        condition.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RequirementLevel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Either a plain scalar, e.g. `required`, or a single entry map
+        // carrying the condition text, e.g. `conditionally_required: <text>`
+        let value: serde_yaml::Value = serde::Deserialize::deserialize(deserializer)?;
+        let (level, text) = match value {
+            serde_yaml::Value::String(s) => (s, None),
+            serde_yaml::Value::Mapping(m) if m.len() == 1 => {
+                let (k, v) = m.into_iter().next().unwrap();
+                match (k, v) {
+                    (serde_yaml::Value::String(k), serde_yaml::Value::String(v)) => (k, Some(v)),
+                    _ => {
+                        return Err(serde::de::Error::custom(
+                            "Failed to parse requirement_level",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(serde::de::Error::custom(
+                    "Failed to parse requirement_level",
+                ))
+            }
+        };
+        match (level.as_str(), text) {
+            ("required", _) => Ok(RequirementLevel::Required),
+            ("recommended", text) => Ok(RequirementLevel::Recommended(text)),
+            ("opt_in", _) => Ok(RequirementLevel::OptIn),
+            ("conditionally_required", Some(text)) => {
+                Ok(RequirementLevel::ConditionallyRequired(text))
+            }
+            _ => Err(serde::de::Error::custom(format!(
+                "Failed to parse requirement_level: {level}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Attribute {
     pub id: Option<String>,
+    pub r#ref: Option<String>,
     pub r#type: Option<Type>,
     pub brief: Option<String>,
     pub note: Option<String>,
     pub examples: Option<Examples>,
+    pub requirement_level: Option<RequirementLevel>,
+    pub stability: Option<Stability>,
     pub deprecated: Option<Deprecated>,
     pub used_by: Option<Vec<String>>,
     pub registry_name: Option<String>,
     pub defined_in: Option<String>,
     pub template_suffixes: Option<BTreeMap<String, Vec<String>>>,
+    pub referenced_by: Option<Vec<String>>,
+    pub type_mismatches: Option<Vec<TypeMismatch>>,
+    /// Earlier definitions of the same key, e.g. from another registry.
+    pub other_definitions: Option<Vec<Attribute>>,
+}
+
+/// A Honeycomb column whose type can't hold the attribute's values.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TypeMismatch {
+    pub dataset: String,
+    pub column: String,
+    pub column_type: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct Deprecated {
-    pub reason: String,
+#[derive(Debug, Clone, PartialEq)]
+pub enum Deprecated {
+    Renamed {
+        renamed_to: String,
+        note: Option<String>,
+    },
+    Obsoleted {
+        note: Option<String>,
+    },
+    Uncategorized {
+        note: Option<String>,
+    },
+    /// The legacy form: a plain string, or a map with a free text `reason`.
+    Text(String),
+}
+
+impl Deprecated {
+    pub fn note(&self) -> Option<&str> {
+        match self {
+            Deprecated::Renamed { note, .. }
+            | Deprecated::Obsoleted { note }
+            | Deprecated::Uncategorized { note } => note.as_deref(),
+            Deprecated::Text(_) => None,
+        }
+    }
 }
 
 impl Display for Deprecated {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Deprecated: {}", self.reason)
+        match self {
+            Deprecated::Renamed { renamed_to, .. } => {
+                write!(f, "Deprecated: renamed to {renamed_to}")
+            }
+            Deprecated::Obsoleted { .. } => write!(f, "Deprecated: obsoleted"),
+            Deprecated::Uncategorized { .. } => write!(f, "Deprecated"),
+            Deprecated::Text(text) => write!(f, "Deprecated: {text}"),
+        }
     }
 }
 
-impl AsRef<str> for Deprecated {
-    fn as_ref(&self) -> &str {
-        &self.reason
+impl Serialize for Deprecated {
+    /// Mirror the YAML form: the legacy plain string, or a map with a `reason`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Structured<'a> {
+            reason: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            renamed_to: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            note: Option<&'a str>,
+        }
+
+        let (reason, renamed_to) = match self {
+            Deprecated::Renamed { renamed_to, .. } => ("renamed", Some(renamed_to.as_str())),
+            Deprecated::Obsoleted { .. } => ("obsoleted", None),
+            Deprecated::Uncategorized { .. } => ("uncategorized", None),
+            Deprecated::Text(text) => return serializer.serialize_str(text),
+        };
+        Structured {
+            reason,
+            renamed_to,
+            note: self.note(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Deprecated {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Structured {
+            reason: String,
+            renamed_to: Option<String>,
+            note: Option<String>,
+        }
+
+        let value: serde_yaml::Value = serde::Deserialize::deserialize(deserializer)?;
+        if let serde_yaml::Value::String(text) = value {
+            return Ok(Deprecated::Text(text));
+        }
+        let structured: Structured =
+            serde_yaml::from_value(value).map_err(serde::de::Error::custom)?;
+        match (structured.reason.as_str(), structured.renamed_to) {
+            ("renamed", Some(renamed_to)) => Ok(Deprecated::Renamed {
+                renamed_to,
+                note: structured.note,
+            }),
+            ("renamed", None) => Err(serde::de::Error::custom(
+                "Failed to parse deprecated: renamed requires renamed_to",
+            )),
+            ("obsoleted", _) => Ok(Deprecated::Obsoleted {
+                note: structured.note,
+            }),
+            ("uncategorized", _) => Ok(Deprecated::Uncategorized {
+                note: structured.note,
+            }),
+            _ => Ok(Deprecated::Text(structured.reason)),
+        }
     }
 }
 
@@ -238,12 +511,93 @@ impl Attribute {
     pub fn is_complex_type(&self) -> bool {
         matches!(&self.r#type, Some(Type::Complex(_)))
     }
+
+    /// The fields that differ from another definition of the same attribute.
+    pub fn differences(&self, other: &Attribute) -> Vec<&'static str> {
+        let mut differences = vec![];
+        if self.r#type != other.r#type {
+            differences.push("type");
+        }
+        if self.brief != other.brief {
+            differences.push("brief");
+        }
+        if self.examples != other.examples {
+            differences.push("examples");
+        }
+        differences
+    }
+
+    /// Apply the local overrides from a group's `ref` to this registry
+    /// attribute, returning the effective attribute and the overridden fields.
+    pub fn with_overrides(&self, local: &Attribute) -> (Attribute, Vec<String>) {
+        let mut attribute = self.clone();
+        let mut overrides = vec![];
+        if local.brief.is_some() {
+            attribute.brief = local.brief.clone();
+            overrides.push("brief".to_owned());
+        }
+        if local.requirement_level.is_some() {
+            attribute.requirement_level = local.requirement_level.clone();
+            overrides.push("requirement level".to_owned());
+        }
+        if local.examples.is_some() {
+            attribute.examples = local.examples.clone();
+            overrides.push("examples".to_owned());
+        }
+        if local.note.is_some() {
+            attribute.note = local.note.clone();
+            overrides.push("note".to_owned());
+        }
+        (attribute, overrides)
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Group {
-    prefix: Option<String>,
-    attributes: Option<Vec<Attribute>>,
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Group {
+    pub id: String,
+    pub r#type: Option<String>,
+    pub brief: Option<String>,
+    pub note: Option<String>,
+    pub prefix: Option<String>,
+    pub span_kind: Option<String>,
+    pub metric_name: Option<String>,
+    pub instrument: Option<String>,
+    pub unit: Option<String>,
+    pub name: Option<String>,
+    pub stability: Option<Stability>,
+    pub extends: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    pub registry_name: Option<String>,
+    pub defined_in: Option<String>,
+    #[serde(skip_deserializing)]
+    pub resolved_attributes: Vec<GroupAttribute>,
+}
+
+/// An attribute as it applies within a group, after resolving `ref`s
+/// and applying the group's local overrides.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupAttribute {
+    pub key: String,
+    pub attribute: Attribute,
+    pub is_ref: bool,
+    pub overrides: Vec<String>,
+    pub inherited_from: Option<String>,
+}
+
+impl Group {
+    /// The fully qualified key of an attribute defined in this group.
+    pub fn attribute_key(&self, attribute: &Attribute) -> Option<String> {
+        let id = attribute.id.as_ref()?;
+        match &self.prefix {
+            Some(prefix) => Some(format!("{prefix}.{id}")),
+            None => Some(id.clone()),
+        }
+    }
+
+    pub fn get_type(&self) -> &str {
+        self.r#type.as_deref().unwrap_or("span")
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -251,61 +605,652 @@ struct Groups {
     groups: Vec<Group>,
 }
 
-#[derive(Debug)]
+/// A model file that failed to load, or that defines a group already
+/// defined elsewhere.
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    pub registry_name: String,
+    pub defined_in: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(registry_name: &str, defined_in: &str, error: &anyhow::Error) -> Self {
+        let location = error
+            .downcast_ref::<serde_yaml::Error>()
+            .and_then(|e| e.location());
+        Problem {
+            registry_name: registry_name.to_owned(),
+            defined_in: defined_in.to_owned(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.registry_name, self.defined_in)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{line}:{column}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A registry character's models and the manifest describing them.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Registry {
+    pub sources: Vec<String>,
+    pub manifest: Option<Manifest>,
+}
+
+/// The attributes whose keys start with `prefix`, e.g. `http.`, sorted by key.
+pub fn attributes_with_prefix<'a>(
+    attribute_map: &'a HashMap<String, Attribute>,
+    prefix: Option<&str>,
+) -> Vec<(&'a str, &'a Attribute)> {
+    let mut attributes: Vec<(&str, &Attribute)> = attribute_map
+        .iter()
+        .filter(|(key, _)| key.starts_with(prefix.unwrap_or_default()))
+        .map(|(key, attribute)| (key.as_str(), attribute))
+        .collect();
+    attributes.sort_by_key(|(key, _)| *key);
+    attributes
+}
+
+/// Parse `(key, yaml)` pairs into an attribute map for tests.
+#[cfg(test)]
+pub fn attribute_map(attributes: &[(&str, &str)]) -> HashMap<String, Attribute> {
+    attributes
+        .iter()
+        .map(|(key, yaml)| (key.to_string(), serde_yaml::from_str(yaml).unwrap()))
+        .collect()
+}
+
+#[derive(Debug, Clone)]
 pub struct SemanticConventions {
     pub attribute_map: HashMap<String, Attribute>,
+    pub groups: BTreeMap<String, Group>,
+    /// Files that couldn't be read, skipped so the rest of the model loads,
+    /// and groups defined more than once.
+    pub problems: Vec<Problem>,
+    pub registries: BTreeMap<String, Registry>,
 }
 
 impl SemanticConventions {
-    pub fn new(root_dirs: &[(String, String)]) -> anyhow::Result<Self> {
+    pub fn new(sources: &[(String, ModelSource)]) -> anyhow::Result<Self> {
         let mut sc = SemanticConventions {
             attribute_map: HashMap::new(),
+            groups: BTreeMap::new(),
+            problems: vec![],
+            registries: BTreeMap::new(),
         };
-        for (registry_name, root_dir) in root_dirs {
-            let yml = format!("{root_dir}/**/*.yml");
-            let yaml = format!("{root_dir}/**/*.yaml");
-            for entry in glob(yml.as_str())?.chain(glob(yaml.as_str())?) {
-                let entry = entry?;
-                // print the trailing part of the path after the root_dir
-                let defined_in = entry.strip_prefix(root_dir)?.to_str().unwrap_or("");
-                // ignore registry_manifest files
+        for (registry_name, source) in sources {
+            let registry = sc.registries.entry(registry_name.clone()).or_default();
+            registry.sources.push(source.to_string());
+            for defined_in in source.files()? {
+                // only the manifest at the root of a model describes it
                 if defined_in.contains("registry_manifest") {
+                    if is_manifest(&defined_in) {
+                        match Manifest::open(source, &defined_in) {
+                            Ok(manifest) => {
+                                if let Some(registry) = sc.registries.get_mut(registry_name) {
+                                    registry.manifest = registry.manifest.take().or(Some(manifest));
+                                }
+                            }
+                            Err(e) => {
+                                sc.problems
+                                    .push(Problem::new(registry_name, &defined_in, &e))
+                            }
+                        }
+                    }
                     continue;
                 }
-                sc.read_file(&entry, registry_name, defined_in)?;
+                let read = source
+                    .open(&defined_in)
+                    .and_then(|reader| sc.read_file(reader, registry_name, &defined_in));
+                if let Err(e) = read {
+                    sc.problems
+                        .push(Problem::new(registry_name, &defined_in, &e));
+                }
             }
         }
+        sc.resolve_refs();
+        sc.resolve_extends()?;
         Ok(sc)
     }
 
     pub fn read_file(
         &mut self,
-        path: &PathBuf,
+        reader: impl Read,
         registry_name: &str,
         defined_in: &str,
     ) -> anyhow::Result<()> {
-        //println!("reading file: {:?}", path);
-        let groups: Groups = serde_yaml::from_reader(&File::open(path)?)?;
-        for group in groups.groups {
-            if let Some(attributes) = group.attributes {
-                for mut attribute in attributes {
-                    let is_some = attribute.id.is_some();
-                    if is_some {
-                        attribute.defined_in = Some(defined_in.to_owned());
-                        attribute.registry_name = Some(registry_name.to_owned());
-                        self.attribute_map.insert(
-                            match &group.prefix {
-                                Some(prefix) => {
-                                    format!("{}.{}", prefix, attribute.id.clone().unwrap())
-                                }
-                                None => attribute.id.clone().unwrap(),
-                            },
-                            attribute,
+        let groups: Groups = serde_yaml::from_reader(reader)?;
+        self.add_groups(groups, registry_name, defined_in);
+        Ok(())
+    }
+
+    fn add_groups(&mut self, groups: Groups, registry_name: &str, defined_in: &str) {
+        for mut group in groups.groups {
+            group.defined_in = Some(defined_in.to_owned());
+            group.registry_name = Some(registry_name.to_owned());
+            for attribute in &group.attributes {
+                if let Some(key) = group.attribute_key(attribute) {
+                    let mut attribute = attribute.clone();
+                    attribute.defined_in = Some(defined_in.to_owned());
+                    attribute.registry_name = Some(registry_name.to_owned());
+                    // keep every definition, the last one read is shown first
+                    if let Some(mut previous) = self.attribute_map.remove(&key) {
+                        let mut others = previous.other_definitions.take().unwrap_or_default();
+                        others.push(previous);
+                        attribute.other_definitions = Some(others);
+                    }
+                    self.attribute_map.insert(key, attribute);
+                }
+            }
+            // like attributes, the last definition read wins, but unlike them
+            // the one it replaces isn't kept, so report it
+            if let Some(previous) = self.groups.insert(group.id.clone(), group) {
+                let error = anyhow::anyhow!(
+                    "group {} is also defined in {} {}, which this definition replaces",
+                    previous.id,
+                    previous.registry_name.as_deref().unwrap_or(""),
+                    previous.defined_in.as_deref().unwrap_or("")
+                );
+                self.problems
+                    .push(Problem::new(registry_name, defined_in, &error));
+            }
+        }
+    }
+
+    /// Describe every attribute defined more than once with a different
+    /// type, brief or examples.
+    pub fn conflicts(&self) -> Vec<String> {
+        let location = |attribute: &Attribute| {
+            format!(
+                "{} {}",
+                attribute.registry_name.as_deref().unwrap_or(""),
+                attribute.defined_in.as_deref().unwrap_or("")
+            )
+        };
+        let mut conflicts = vec![];
+        for (key, attribute) in &self.attribute_map {
+            for other in attribute.other_definitions.iter().flatten() {
+                let differences = attribute.differences(other);
+                if !differences.is_empty() {
+                    conflicts.push(format!(
+                        "{key} has a different {} in {} and {}",
+                        differences.join(", "),
+                        location(other),
+                        location(attribute)
+                    ));
+                }
+            }
+        }
+        conflicts.sort();
+        conflicts
+    }
+
+    /// Link each group's attributes back to the registry, applying local
+    /// overrides for `ref`s and recording which groups reference each attribute.
+    fn resolve_refs(&mut self) {
+        for group in self.groups.values_mut() {
+            let mut resolved_attributes = vec![];
+            for attribute in &group.attributes {
+                if let Some(key) = group.attribute_key(attribute) {
+                    let mut attribute = attribute.clone();
+                    attribute.defined_in = group.defined_in.clone();
+                    attribute.registry_name = group.registry_name.clone();
+                    resolved_attributes.push(GroupAttribute {
+                        key,
+                        attribute,
+                        is_ref: false,
+                        overrides: vec![],
+                        inherited_from: None,
+                    });
+                } else if let Some(key) = &attribute.r#ref {
+                    let Some(registry_attribute) = self.attribute_map.get_mut(key) else {
+                        eprintln!(
+                            "Group {} ({}) references unknown attribute {key}",
+                            group.id,
+                            group.defined_in.as_deref().unwrap_or("")
                         );
+                        continue;
+                    };
+                    let (effective, overrides) = registry_attribute.with_overrides(attribute);
+                    resolved_attributes.push(GroupAttribute {
+                        key: key.clone(),
+                        attribute: effective,
+                        is_ref: true,
+                        overrides,
+                        inherited_from: None,
+                    });
+                    match registry_attribute.referenced_by {
+                        Some(ref mut referenced_by) => referenced_by.push(group.id.clone()),
+                        None => registry_attribute.referenced_by = Some(vec![group.id.clone()]),
                     }
                 }
             }
+            group.resolved_attributes = resolved_attributes;
+        }
+    }
+
+    /// Second resolution pass: flatten `extends` chains so each group holds
+    /// its full effective attribute set.
+    fn resolve_extends(&mut self) -> anyhow::Result<()> {
+        let mut flattened = BTreeMap::new();
+        for id in self.groups.keys() {
+            self.flatten_group(id, &mut flattened, &mut vec![])?;
+        }
+        for (id, resolved_attributes) in flattened {
+            let group = self.groups.get_mut(&id).unwrap();
+            // groups inheriting an attribute also reference it
+            for resolved in &resolved_attributes {
+                if resolved.inherited_from.is_none() {
+                    continue;
+                }
+                if let Some(attribute) = self.attribute_map.get_mut(&resolved.key) {
+                    let referenced_by = attribute.referenced_by.get_or_insert_with(Vec::new);
+                    if !referenced_by.contains(&id) {
+                        referenced_by.push(id.clone());
+                    }
+                }
+            }
+            group.resolved_attributes = resolved_attributes;
         }
         Ok(())
     }
+
+    fn flatten_group(
+        &self,
+        id: &str,
+        flattened: &mut BTreeMap<String, Vec<GroupAttribute>>,
+        chain: &mut Vec<String>,
+    ) -> anyhow::Result<Vec<GroupAttribute>> {
+        if let Some(resolved_attributes) = flattened.get(id) {
+            return Ok(resolved_attributes.clone());
+        }
+        let group = &self.groups[id];
+        let defined_in = group.defined_in.as_deref().unwrap_or("");
+        if chain.iter().any(|c| c == id) {
+            anyhow::bail!(
+                "Group {id} in {defined_in} has a cyclic extends chain: {} -> {id}",
+                chain.join(" -> ")
+            );
+        }
+        let Some(parent_id) = &group.extends else {
+            flattened.insert(id.to_owned(), group.resolved_attributes.clone());
+            return Ok(group.resolved_attributes.clone());
+        };
+        if !self.groups.contains_key(parent_id) {
+            anyhow::bail!("Group {id} in {defined_in} extends unknown group {parent_id}");
+        }
+
+        chain.push(id.to_owned());
+        let inherited = self.flatten_group(parent_id, flattened, chain)?;
+        chain.pop();
+
+        let mut resolved_attributes: Vec<GroupAttribute> = inherited
+            .into_iter()
+            .map(|mut resolved| {
+                if resolved.inherited_from.is_none() {
+                    resolved.inherited_from = Some(parent_id.clone());
+                }
+                resolved
+            })
+            .collect();
+        for own in &group.resolved_attributes {
+            let existing = resolved_attributes.iter_mut().find(|r| r.key == own.key);
+            let local = group
+                .attributes
+                .iter()
+                .find(|a| a.r#ref.as_ref() == Some(&own.key));
+            match (existing, local) {
+                (Some(existing), Some(local)) => {
+                    // layer this group's overrides on top of the inherited ones
+                    let (attribute, overrides) = existing.attribute.with_overrides(local);
+                    for o in overrides {
+                        if !existing.overrides.contains(&o) {
+                            existing.overrides.push(o);
+                        }
+                    }
+                    existing.attribute = attribute;
+                    existing.inherited_from = None;
+                }
+                (Some(existing), None) => *existing = own.clone(),
+                (None, _) => resolved_attributes.push(own.clone()),
+            }
+        }
+        flattened.insert(id.to_owned(), resolved_attributes.clone());
+        Ok(resolved_attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conventions(files: &[&str]) -> SemanticConventions {
+        let mut sc = SemanticConventions {
+            attribute_map: HashMap::new(),
+            groups: BTreeMap::new(),
+            problems: vec![],
+            registries: BTreeMap::new(),
+        };
+        for (i, yaml) in files.iter().enumerate() {
+            let groups: Groups = serde_yaml::from_str(yaml).unwrap();
+            sc.add_groups(groups, "t", &format!("file{i}.yaml"));
+        }
+        sc.resolve_refs();
+        sc
+    }
+
+    fn extends_conventions(files: &[&str]) -> anyhow::Result<SemanticConventions> {
+        let mut sc = conventions(files);
+        sc.resolve_extends()?;
+        Ok(sc)
+    }
+
+    #[test]
+    fn test_resolve_refs() {
+        let sc = conventions(&[
+            r#"
+            groups:
+              - id: registry.http
+                type: attribute_group
+                attributes:
+                  - id: http.request.method
+                    type: string
+                    brief: HTTP request method.
+            "#,
+            r#"
+            groups:
+              - id: span.http.client
+                type: span
+                attributes:
+                  - ref: http.request.method
+                    requirement_level: required
+                  - ref: http.unknown
+            "#,
+        ]);
+        let group = &sc.groups["span.http.client"];
+        assert_eq!(group.resolved_attributes.len(), 1);
+        let resolved = &group.resolved_attributes[0];
+        assert!(resolved.is_ref);
+        assert_eq!(resolved.overrides, vec!["requirement level"]);
+        assert_eq!(
+            resolved.attribute.requirement_level,
+            Some(RequirementLevel::Required)
+        );
+        assert_eq!(
+            resolved.attribute.brief.as_deref(),
+            Some("HTTP request method.")
+        );
+        assert_eq!(
+            sc.attribute_map["http.request.method"].referenced_by,
+            Some(vec!["span.http.client".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_resolve_extends() {
+        let sc = extends_conventions(&[r#"
+            groups:
+              - id: registry.http
+                type: attribute_group
+                attributes:
+                  - id: http.request.method
+                    type: string
+                    brief: HTTP request method.
+                  - id: http.response.status_code
+                    type: int
+              - id: attributes.http.common
+                type: attribute_group
+                attributes:
+                  - ref: http.request.method
+                    requirement_level: required
+                  - ref: http.response.status_code
+              - id: span.http.client
+                type: span
+                extends: attributes.http.common
+                attributes:
+                  - ref: http.request.method
+                    brief: Client method.
+            "#])
+        .unwrap();
+        let group = &sc.groups["span.http.client"];
+        assert_eq!(group.resolved_attributes.len(), 2);
+        let method = &group.resolved_attributes[0];
+        assert_eq!(method.key, "http.request.method");
+        assert_eq!(method.inherited_from, None);
+        assert_eq!(method.overrides, vec!["requirement level", "brief"]);
+        assert_eq!(method.attribute.brief.as_deref(), Some("Client method."));
+        let status_code = &group.resolved_attributes[1];
+        assert_eq!(
+            status_code.inherited_from.as_deref(),
+            Some("attributes.http.common")
+        );
+        assert!(sc.attribute_map["http.response.status_code"]
+            .referenced_by
+            .as_ref()
+            .unwrap()
+            .contains(&"span.http.client".to_owned()));
+    }
+
+    #[test]
+    fn test_resolve_extends_errors() {
+        let err = extends_conventions(&[r#"
+            groups:
+              - id: a
+                extends: b
+              - id: b
+                extends: a
+            "#])
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Group a in file0.yaml has a cyclic extends chain: a -> b -> a"
+        );
+
+        let err = extends_conventions(&[r#"
+            groups:
+              - id: a
+                extends: missing
+            "#])
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Group a in file0.yaml extends unknown group missing"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_are_collected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("good.yaml"),
+            "groups:\n  - id: registry.foo\n    prefix: foo\n    attributes:\n      - id: bar\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("bad.yaml"), "groups:\n  - id: [\n").unwrap();
+        let source = ModelSource::parse(dir.to_str().unwrap()).unwrap();
+        let sc = SemanticConventions::new(&[("t".to_owned(), source)]).unwrap();
+
+        assert!(sc.attribute_map.contains_key("foo.bar"));
+        assert_eq!(sc.problems.len(), 1);
+        let problem = &sc.problems[0];
+        assert_eq!(problem.defined_in, "bad.yaml");
+        assert_eq!(problem.line, Some(2));
+        assert!(problem.to_string().starts_with("t bad.yaml:2:"));
+    }
+
+    #[test]
+    fn test_duplicate_definitions() {
+        let sc = conventions(&[
+            r#"
+            groups:
+              - id: registry.http
+                prefix: http
+                attributes:
+                  - id: route
+                    type: string
+                    brief: The matched route.
+                  - id: method
+                    type: string
+            "#,
+            r#"
+            groups:
+              - id: registry.http.other
+                prefix: http
+                attributes:
+                  - id: route
+                    type: string
+                    brief: The route template.
+                  - id: method
+                    type: string
+            "#,
+        ]);
+        let route = &sc.attribute_map["http.route"];
+        assert_eq!(route.defined_in.as_deref(), Some("file1.yaml"));
+        let others = route.other_definitions.as_ref().unwrap();
+        assert_eq!(others[0].defined_in.as_deref(), Some("file0.yaml"));
+        assert_eq!(route.differences(&others[0]), vec!["brief"]);
+        assert_eq!(
+            sc.conflicts(),
+            vec!["http.route has a different brief in t file0.yaml and t file1.yaml"]
+        );
+        assert!(sc.problems.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_group() {
+        let sc = conventions(&[
+            r#"
+            groups:
+              - id: registry.http
+                prefix: http
+                attributes:
+                  - id: route
+                    type: string
+            "#,
+            r#"
+            groups:
+              - id: registry.http
+                prefix: http
+                attributes:
+                  - id: method
+                    type: string
+            "#,
+        ]);
+        assert_eq!(
+            sc.groups["registry.http"].defined_in.as_deref(),
+            Some("file1.yaml")
+        );
+        assert_eq!(sc.problems.len(), 1);
+        assert_eq!(
+            sc.problems[0].to_string(),
+            "t file1.yaml: group registry.http is also defined in t file0.yaml, which this definition replaces"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_forms() {
+        let deprecated = |yaml: &str| {
+            serde_yaml::from_str::<Attribute>(yaml)
+                .map(|attribute| attribute.deprecated.unwrap())
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            deprecated("id: foo\ndeprecated: Replaced by `bar`."),
+            Ok(Deprecated::Text("Replaced by `bar`.".to_owned()))
+        );
+        assert_eq!(
+            deprecated("id: foo\ndeprecated:\n  reason: Replaced by `bar`."),
+            Ok(Deprecated::Text("Replaced by `bar`.".to_owned()))
+        );
+        assert_eq!(
+            deprecated("id: foo\ndeprecated:\n  reason: renamed\n  renamed_to: bar"),
+            Ok(Deprecated::Renamed {
+                renamed_to: "bar".to_owned(),
+                note: None
+            })
+        );
+        assert_eq!(
+            deprecated("id: foo\ndeprecated:\n  reason: obsoleted\n  note: Gone."),
+            Ok(Deprecated::Obsoleted {
+                note: Some("Gone.".to_owned())
+            })
+        );
+        assert_eq!(
+            deprecated("id: foo\ndeprecated:\n  reason: uncategorized"),
+            Ok(Deprecated::Uncategorized { note: None })
+        );
+        assert!(deprecated("id: foo\ndeprecated:\n  reason: renamed").is_err());
+    }
+
+    #[test]
+    fn test_stability() {
+        let attribute: Attribute =
+            serde_yaml::from_str("id: foo\nstability: release_candidate").unwrap();
+        assert_eq!(attribute.stability, Some(Stability::ReleaseCandidate));
+        assert!(serde_yaml::from_str::<Attribute>("id: foo\nstability: unknown").is_err());
+    }
+
+    #[test]
+    fn test_requirement_level_scalar() {
+        let attribute: Attribute =
+            serde_yaml::from_str("id: foo\nrequirement_level: opt_in").unwrap();
+        assert_eq!(attribute.requirement_level, Some(RequirementLevel::OptIn));
+    }
+
+    #[test]
+    fn test_requirement_level_conditional() {
+        let attribute: Attribute = serde_yaml::from_str(
+            "id: foo\nrequirement_level:\n  conditionally_required: If `bar` is set.",
+        )
+        .unwrap();
+        let level = attribute.requirement_level.unwrap();
+        assert_eq!(level.to_string(), "conditionally required");
+        assert_eq!(level.condition(), Some("If `bar` is set."));
+    }
+
+    #[test]
+    fn test_requirement_level_invalid() {
+        assert!(serde_yaml::from_str::<Attribute>("id: foo\nrequirement_level: maybe").is_err());
+        assert!(serde_yaml::from_str::<Attribute>(
+            "id: foo\nrequirement_level:\n  conditionally_required: [a]"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_serialize_mirrors_yaml() {
+        let yaml = "id: foo\n\
+                    type: template[string[]]\n\
+                    requirement_level:\n  conditionally_required: If available.\n\
+                    stability: release_candidate\n\
+                    deprecated:\n  reason: renamed\n  renamed_to: bar\n";
+        let attribute: Attribute = serde_yaml::from_str(yaml).unwrap();
+        let json = serde_json::to_value(&attribute).unwrap();
+        assert_eq!(json["type"], "template[string[]]");
+        assert_eq!(
+            json["requirement_level"],
+            serde_json::json!({"conditionally_required": "If available."})
+        );
+        assert_eq!(json["stability"], "release_candidate");
+        assert_eq!(
+            json["deprecated"],
+            serde_json::json!({"reason": "renamed", "renamed_to": "bar"})
+        );
+
+        let round_trip: Attribute = serde_yaml::from_str(&json.to_string()).unwrap();
+        assert_eq!(round_trip.requirement_level, attribute.requirement_level);
+        assert_eq!(round_trip.deprecated, attribute.deprecated);
+    }
 }
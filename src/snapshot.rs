@@ -0,0 +1,102 @@
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path, sync::Arc};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use honeycomb_client::honeycomb::HoneyComb;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Column {
+    pub key_name: String,
+    pub r#type: String,
+    /// Further types the OTLP receiver saw values of. A Honeycomb column
+    /// has one type, but a service can send a key with several.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_types: Vec<String>,
+}
+
+impl Column {
+    pub fn new(key_name: &str, r#type: &str) -> Self {
+        Column {
+            key_name: key_name.to_owned(),
+            r#type: r#type.to_owned(),
+            other_types: vec![],
+        }
+    }
+
+    /// Every type the column's values have been seen with.
+    pub fn types(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.r#type).chain(&self.other_types)
+    }
+}
+
+/// The columns found in each Honeycomb dataset. This can be saved to
+/// and loaded from a JSON file to start without querying Honeycomb. Each
+/// dataset's columns are shared, so a copy of the snapshot only copies the
+/// datasets that then change.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub created_at: DateTime<Utc>,
+    pub datasets: BTreeMap<String, Arc<Vec<Column>>>,
+}
+
+impl Snapshot {
+    /// Read the columns of every dataset written to in the last 30 days.
+    pub async fn from_honeycomb(hc: &HoneyComb) -> anyhow::Result<Self> {
+        let mut snapshot = Snapshot {
+            created_at: Utc::now(),
+            datasets: BTreeMap::new(),
+        };
+        let dataset_slugs = hc.get_dataset_slugs(30, None).await?;
+        eprint!("Reading {} datasets ", dataset_slugs.len());
+        hc.process_datasets_columns(30, &dataset_slugs, |dataset, columns| {
+            eprint!(".");
+            snapshot.datasets.insert(
+                dataset.clone(),
+                Arc::new(
+                    columns
+                        .iter()
+                        .map(|column| Column::new(&column.key_name, &column.r#type))
+                        .collect(),
+                ),
+            );
+        })
+        .await?;
+        eprintln!();
+        Ok(snapshot)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open snapshot {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read snapshot {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create snapshot {}", path.display()))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut snapshot = Snapshot::default();
+        snapshot.datasets.insert(
+            "frontend".to_owned(),
+            Arc::new(vec![Column::new("http.request.method", "string")]),
+        );
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("honey-explore-snapshot-test.json");
+        snapshot.save(&path).unwrap();
+        let loaded = Snapshot::load(&path).unwrap();
+        assert_eq!(loaded.created_at, snapshot.created_at);
+        assert_eq!(loaded.datasets, snapshot.datasets);
+    }
+}
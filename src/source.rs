@@ -0,0 +1,501 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use glob::glob;
+
+/// Where a registry's model files are read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelSource {
+    /// A directory on disk.
+    Directory(String),
+    /// The yaml files in a directory of a local git repository, read into
+    /// memory as of a ref without checking it out.
+    Git {
+        dir: String,
+        git_ref: String,
+        files: Arc<Vec<(String, Vec<u8>)>>,
+    },
+    /// A single yaml file.
+    File(String),
+    /// The yaml files in a `.tar.gz` or `.zip` archive, read into memory.
+    Archive {
+        path: String,
+        files: Arc<Vec<(String, Vec<u8>)>>,
+    },
+}
+
+impl ModelSource {
+    /// Parse the part of a model path after the registry character: a
+    /// directory, optionally followed by `@<git ref>`, a yaml file, or an
+    /// archive, optionally followed by the directory to read inside it.
+    pub fn parse(path: &str) -> anyhow::Result<Self> {
+        let p = Path::new(path);
+        if p.is_dir() {
+            return Ok(ModelSource::Directory(canonical(p)?));
+        }
+        if p.is_file() && is_yaml(path) {
+            return Ok(ModelSource::File(canonical(p)?));
+        }
+        if let Some(archive) = p.ancestors().find(|a| a.is_file() && is_archive(a)) {
+            let subdir = p.strip_prefix(archive)?;
+            let files = read_archive(archive, subdir)
+                .with_context(|| format!("failed to read {}", archive.display()))?;
+            return Ok(ModelSource::Archive {
+                path: canonical(archive)?,
+                files: Arc::new(files),
+            });
+        }
+        let Some((dir, git_ref)) = path.rsplit_once('@') else {
+            anyhow::bail!("{} is not a directory, yaml file or archive", path);
+        };
+        let p = Path::new(dir);
+        if !p.is_dir() {
+            anyhow::bail!("{} is not directory", dir);
+        }
+        let dir = canonical(p)?;
+        git(
+            &dir,
+            &["rev-parse", "--verify", &format!("{git_ref}^{{commit}}")],
+        )
+        .with_context(|| format!("{git_ref} is not a commit in {dir}"))?;
+        let files = read_git(&dir, git_ref)
+            .with_context(|| format!("failed to read {dir} at {git_ref}"))?;
+        Ok(ModelSource::Git {
+            dir,
+            git_ref: git_ref.to_owned(),
+            files: Arc::new(files),
+        })
+    }
+
+    /// The yaml files in the model, relative to its root.
+    pub fn files(&self) -> anyhow::Result<Vec<String>> {
+        match self {
+            ModelSource::Directory(root_dir) => {
+                let yml = format!("{root_dir}/**/*.yml");
+                let yaml = format!("{root_dir}/**/*.yaml");
+                let mut files = vec![];
+                for entry in glob(yml.as_str())?.chain(glob(yaml.as_str())?) {
+                    let entry = entry?;
+                    let defined_in = entry.strip_prefix(root_dir)?.to_str().unwrap_or("");
+                    files.push(defined_in.to_owned());
+                }
+                Ok(files)
+            }
+            ModelSource::File(path) => {
+                let file_name = Path::new(path).file_name().context("invalid path")?;
+                Ok(vec![file_name.to_string_lossy().into_owned()])
+            }
+            ModelSource::Git { files, .. } | ModelSource::Archive { files, .. } => {
+                Ok(files.iter().map(|(name, _)| name.clone()).collect())
+            }
+        }
+    }
+
+    /// Open one of the files returned by `files`.
+    pub fn open(&self, defined_in: &str) -> anyhow::Result<Box<dyn Read + '_>> {
+        match self {
+            ModelSource::Directory(root_dir) => {
+                Ok(Box::new(File::open(Path::new(root_dir).join(defined_in))?))
+            }
+            ModelSource::File(path) => Ok(Box::new(File::open(path)?)),
+            ModelSource::Git { files, .. } | ModelSource::Archive { files, .. } => {
+                let (_, contents) = files
+                    .iter()
+                    .find(|(name, _)| name == defined_in)
+                    .with_context(|| format!("{defined_in} is not in {self}"))?;
+                Ok(Box::new(contents.as_slice()))
+            }
+        }
+    }
+
+    /// The directory to watch for changes, if the files can change.
+    pub fn watch_dir(&self) -> Option<&str> {
+        match self {
+            ModelSource::Directory(root_dir) => Some(root_dir),
+            ModelSource::Git { .. } | ModelSource::File(_) | ModelSource::Archive { .. } => None,
+        }
+    }
+
+    /// The local directory that relative paths in the model, such as a
+    /// manifest's dependencies, are resolved against.
+    pub fn local_root(&self) -> Option<PathBuf> {
+        match self {
+            ModelSource::Directory(dir) | ModelSource::Git { dir, .. } => Some(PathBuf::from(dir)),
+            ModelSource::File(path) | ModelSource::Archive { path, .. } => {
+                Path::new(path).parent().map(Path::to_path_buf)
+            }
+        }
+    }
+
+    /// Whether both sources read the same model, without comparing the
+    /// contents read into memory.
+    pub fn same_location(&self, other: &ModelSource) -> bool {
+        match (self, other) {
+            (ModelSource::Archive { path: a, .. }, ModelSource::Archive { path: b, .. }) => a == b,
+            (
+                ModelSource::Git {
+                    dir: a,
+                    git_ref: a_ref,
+                    ..
+                },
+                ModelSource::Git {
+                    dir: b,
+                    git_ref: b_ref,
+                    ..
+                },
+            ) => a == b && a_ref == b_ref,
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for ModelSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelSource::Directory(path) | ModelSource::File(path) => write!(f, "{path}"),
+            ModelSource::Git { dir, git_ref, .. } => write!(f, "{dir}@{git_ref}"),
+            ModelSource::Archive { path, .. } => write!(f, "{path}"),
+        }
+    }
+}
+
+fn is_yaml(name: &str) -> bool {
+    name.ends_with(".yml") || name.ends_with(".yaml")
+}
+
+fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".tar.gz") || name.ends_with(".tgz") || name.ends_with(".zip")
+}
+
+/// Read the yaml files below `subdir` in an archive, named relative to it.
+/// Release archives wrap everything in a single top-level directory, which
+/// is skipped.
+fn read_archive(path: &Path, subdir: &Path) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    let mut entries = vec![];
+    if path.to_string_lossy().ends_with(".zip") {
+        let mut zip = zip::ZipArchive::new(File::open(path)?)?;
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            if !file.is_file() || !is_yaml(file.name()) {
+                continue;
+            }
+            let name = file.name().to_owned();
+            let mut contents = vec![];
+            file.read_to_end(&mut contents)?;
+            entries.push((name, contents));
+        }
+    } else {
+        let mut tar = tar::Archive::new(GzDecoder::new(File::open(path)?));
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            if !entry.header().entry_type().is_file() || !is_yaml(&name) {
+                continue;
+            }
+            let mut contents = vec![];
+            entry.read_to_end(&mut contents)?;
+            entries.push((name, contents));
+        }
+    }
+
+    // normalise the names to their components, e.g. without a leading ./
+    let mut entries: Vec<(Vec<String>, Vec<u8>)> = entries
+        .into_iter()
+        .map(|(name, contents)| {
+            let components = Path::new(&name)
+                .components()
+                .filter_map(|c| match c {
+                    Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+                    _ => None,
+                })
+                .collect();
+            (components, contents)
+        })
+        .collect();
+    let top_level = entries.first().map(|(components, _)| components[0].clone());
+    if entries
+        .iter()
+        .all(|(components, _)| components.len() > 1 && Some(&components[0]) == top_level.as_ref())
+    {
+        for (components, _) in entries.iter_mut() {
+            components.remove(0);
+        }
+    }
+
+    let subdir: Vec<String> = subdir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let mut files: Vec<(String, Vec<u8>)> = entries
+        .into_iter()
+        .filter(|(components, _)| components.starts_with(&subdir))
+        .map(|(components, contents)| (components[subdir.len()..].join("/"), contents))
+        .collect();
+    // match the order of a directory: all .yml files then .yaml
+    files.sort_by(|(a, _), (b, _)| (!a.ends_with(".yml"), a).cmp(&(!b.ends_with(".yml"), b)));
+    Ok(files)
+}
+
+/// Read the yaml files below `dir` as of `git_ref`, named relative to it. One
+/// `git ls-tree` lists the blobs and one `git cat-file --batch` reads them
+/// all, rather than a process per file.
+fn read_git(dir: &str, git_ref: &str) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+    // run from the model directory, ls-tree lists the files below it with
+    // paths relative to it
+    let output = git(dir, &["ls-tree", "-r", "-z", git_ref])?;
+    let mut blobs = vec![];
+    for entry in String::from_utf8(output)?.split('\0') {
+        // <mode> SP <type> SP <object> TAB <file>
+        let Some((info, name)) = entry.split_once('\t') else {
+            continue;
+        };
+        if let [_, "blob", object] = info.split(' ').collect::<Vec<_>>()[..] {
+            if is_yaml(name) {
+                blobs.push((name.to_owned(), object.to_owned()));
+            }
+        }
+    }
+    if blobs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let input: String = blobs
+        .iter()
+        .map(|(_, object)| format!("{object}\n"))
+        .collect();
+    let output = git_with_input(dir, &["cat-file", "--batch"], input.as_bytes())?;
+    let mut rest = output.as_slice();
+    let mut files = vec![];
+    for (name, object) in blobs {
+        // <object> SP <type> SP <size> LF <contents> LF
+        let header_len = rest
+            .iter()
+            .position(|b| *b == b'\n')
+            .context("truncated git cat-file output")?;
+        let header = std::str::from_utf8(&rest[..header_len])?;
+        let size = match header.split(' ').collect::<Vec<_>>()[..] {
+            [_, "blob", size] => size.parse::<usize>()?,
+            _ => anyhow::bail!("failed to read {name} ({object}): {header}"),
+        };
+        let contents = rest
+            .get(header_len + 1..header_len + 1 + size)
+            .context("truncated git cat-file output")?;
+        files.push((name, contents.to_vec()));
+        rest = rest.get(header_len + size + 2..).unwrap_or_default();
+    }
+    // match the order of a directory: all .yml files then .yaml
+    let (yml, yaml): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|(name, _)| name.ends_with(".yml"));
+    Ok(yml.into_iter().chain(yaml).collect())
+}
+
+fn canonical(path: &Path) -> anyhow::Result<String> {
+    Ok(path
+        .canonicalize()?
+        .to_str()
+        .context("invalid path")?
+        .to_owned())
+}
+
+fn git(dir: &str, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    git_with_input(dir, args, &[])
+}
+
+/// Run git with `input` on its standard input, written from another thread
+/// so a large output can't block it.
+fn git_with_input(dir: &str, args: &[&str], input: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git")?;
+    let mut stdin = child.stdin.take().context("failed to run git")?;
+    let output = std::thread::scope(|scope| {
+        scope.spawn(move || std::io::Write::write_all(&mut stdin, input));
+        child.wait_with_output()
+    })
+    .context("failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directory() {
+        let dir = std::env::temp_dir();
+        let source = ModelSource::parse(dir.to_str().unwrap()).unwrap();
+        assert!(matches!(source, ModelSource::Directory(_)));
+        assert!(source.watch_dir().is_some());
+    }
+
+    #[test]
+    fn test_parse_missing() {
+        let err = ModelSource::parse("/no/such/model@v1.0.0").unwrap_err();
+        assert_eq!(err.to_string(), "/no/such/model is not directory");
+        let err = ModelSource::parse("/no/such/model").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/no/such/model is not a directory, yaml file or archive"
+        );
+    }
+
+    #[test]
+    fn test_read_at_git_ref() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = tmp.path();
+        std::fs::create_dir_all(repo.join("model/http")).unwrap();
+        std::fs::write(repo.join("README.md"), "not a model file").unwrap();
+        std::fs::write(repo.join("model/http/registry.yaml"), "groups: []\n").unwrap();
+        // independent of the user's git config, e.g. commit.gpgsign
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(["-c", "commit.gpgsign=false", "-c", "tag.gpgsign=false"])
+                .args(args)
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        let commit = |message: &str| {
+            git(&["add", "-A"]);
+            git(&["commit", "-qm", message]);
+        };
+        git(&["init", "-q"]);
+        commit("first");
+        git(&["tag", "v1"]);
+        std::fs::write(repo.join("model/http/registry.yaml"), "changed").unwrap();
+        std::fs::write(repo.join("model/new.yml"), "added").unwrap();
+        commit("second");
+
+        let model_dir = repo.join("model");
+        let source = ModelSource::parse(&format!("{}@v1", model_dir.display())).unwrap();
+        assert!(source.watch_dir().is_none());
+        assert_eq!(source.files().unwrap(), vec!["http/registry.yaml"]);
+        let mut contents = String::new();
+        source
+            .open("http/registry.yaml")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "groups: []\n");
+
+        let source = ModelSource::parse(&format!("{}@HEAD", model_dir.display())).unwrap();
+        assert_eq!(
+            source.files().unwrap(),
+            vec!["new.yml", "http/registry.yaml"]
+        );
+    }
+
+    fn read_all(source: &ModelSource) -> Vec<(String, String)> {
+        source
+            .files()
+            .unwrap()
+            .into_iter()
+            .map(|name| {
+                let mut contents = String::new();
+                source
+                    .open(&name)
+                    .unwrap()
+                    .read_to_string(&mut contents)
+                    .unwrap();
+                (name, contents)
+            })
+            .collect()
+    }
+
+    const ARCHIVE_FILES: [(&str, &str); 4] = [
+        ("semconv-1.0/README.md", "not a model file"),
+        ("semconv-1.0/model/http/registry.yaml", "http"),
+        ("semconv-1.0/model/db.yml", "db"),
+        ("semconv-1.0/templates/weaver.yaml", "templates"),
+    ];
+
+    #[test]
+    fn test_read_tar_gz() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("honey-explore-archive-test.tar.gz");
+        let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        ));
+        for (name, contents) in ARCHIVE_FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            tar.append_data(&mut header, name, contents.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let source = ModelSource::parse(&format!("{}/model", path.display())).unwrap();
+        assert_eq!(
+            read_all(&source),
+            vec![
+                ("db.yml".to_owned(), "db".to_owned()),
+                ("http/registry.yaml".to_owned(), "http".to_owned()),
+            ]
+        );
+        let source = ModelSource::parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(source.files().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("honey-explore-archive-test.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        for (name, contents) in ARCHIVE_FILES {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut zip, contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let source = ModelSource::parse(&format!("{}/model", path.display())).unwrap();
+        assert_eq!(
+            source.files().unwrap(),
+            vec!["db.yml", "http/registry.yaml"]
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("honey-explore-file-test.yaml");
+        std::fs::write(&path, "groups: []\n").unwrap();
+        let source = ModelSource::parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            read_all(&source),
+            vec![(
+                "honey-explore-file-test.yaml".to_owned(),
+                "groups: []\n".to_owned()
+            )]
+        );
+    }
+}
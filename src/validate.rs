@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    coverage,
+    otlp::{AnyValue, OtlpAttribute},
+    semconv::{Attribute, Deprecated, MemberValue, PrimitiveType, Type},
+};
+
+/// An attribute in the telemetry that doesn't follow the conventions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub line: usize,
+    pub context: String,
+    pub key: String,
+    pub message: String,
+}
+
+/// Whether an OTLP value can be a value of the convention's type. Whole
+/// numbers are accepted for doubles.
+fn is_valid_type(r#type: &Type, value: &AnyValue) -> bool {
+    let scalar = |primitive: &PrimitiveType, value: &AnyValue| match primitive {
+        PrimitiveType::String => matches!(value, AnyValue::String(_)),
+        PrimitiveType::Int => matches!(value, AnyValue::Int(_)),
+        PrimitiveType::Double => matches!(value, AnyValue::Double(_) | AnyValue::Int(_)),
+        PrimitiveType::Boolean => matches!(value, AnyValue::Bool(_)),
+        _ => true,
+    };
+    let array = |primitive: &PrimitiveType| match value {
+        AnyValue::Array(values) => values.iter().all(|value| scalar(primitive, value)),
+        _ => false,
+    };
+    match r#type {
+        Type::Simple(primitive) => match primitive {
+            PrimitiveType::Any => true,
+            PrimitiveType::String | PrimitiveType::TemplateOfString => {
+                scalar(&PrimitiveType::String, value)
+            }
+            PrimitiveType::Int | PrimitiveType::TemplateOfInt => scalar(&PrimitiveType::Int, value),
+            PrimitiveType::Double | PrimitiveType::TemplateOfDouble => {
+                scalar(&PrimitiveType::Double, value)
+            }
+            PrimitiveType::Boolean | PrimitiveType::TemplateOfBoolean => {
+                scalar(&PrimitiveType::Boolean, value)
+            }
+            PrimitiveType::ArrayOfString | PrimitiveType::TemplateOfArrayOfString => {
+                array(&PrimitiveType::String)
+            }
+            PrimitiveType::ArrayOfInt | PrimitiveType::TemplateOfArrayOfInt => {
+                array(&PrimitiveType::Int)
+            }
+            PrimitiveType::ArrayOfDouble | PrimitiveType::TemplateOfArrayOfDouble => {
+                array(&PrimitiveType::Double)
+            }
+            PrimitiveType::ArrayOfBoolean | PrimitiveType::TemplateOfArrayOfBoolean => {
+                array(&PrimitiveType::Boolean)
+            }
+        },
+        Type::Complex(complex) => complex.members.iter().any(|member| {
+            matches!(
+                (&member.value, value),
+                (MemberValue::StringType(_), AnyValue::String(_))
+                    | (MemberValue::IntegerType(_), AnyValue::Int(_))
+            )
+        }),
+    }
+}
+
+fn is_member(member: &MemberValue, value: &AnyValue) -> bool {
+    match (member, value) {
+        (MemberValue::StringType(m), AnyValue::String(v)) => m == v,
+        (MemberValue::IntegerType(m), AnyValue::Int(v)) => m == v,
+        _ => false,
+    }
+}
+
+/// The ways an attribute breaks the conventions.
+fn check(attribute: &OtlpAttribute, attribute_map: &HashMap<String, Attribute>) -> Vec<String> {
+    let Some((key, convention)) = coverage::matching_attribute(attribute_map, &attribute.key)
+    else {
+        return vec!["is not defined by any convention".to_owned()];
+    };
+    let mut messages = vec![];
+    if convention.is_template_type() && *key == attribute.key {
+        messages.push(format!(
+            "is a template attribute, add a key to it, e.g. {key}.<key>"
+        ));
+    }
+    if let Some(deprecated) = &convention.deprecated {
+        messages.push(match deprecated {
+            Deprecated::Renamed { renamed_to, .. } => {
+                format!("is deprecated: renamed to {renamed_to}")
+            }
+            Deprecated::Obsoleted { .. } => "is deprecated: obsoleted".to_owned(),
+            Deprecated::Uncategorized { .. } => "is deprecated".to_owned(),
+            Deprecated::Text(text) => format!("is deprecated: {}", text.trim()),
+        });
+    }
+    if let Some(r#type) = &convention.r#type {
+        if !is_valid_type(r#type, &attribute.value) {
+            messages.push(format!(
+                "is {} but the convention's type is {type}",
+                attribute.value.type_name()
+            ));
+        } else if let Type::Complex(complex) = r#type {
+            if !complex.allow_custom_values
+                && !complex
+                    .members
+                    .iter()
+                    .any(|member| is_member(&member.value, &attribute.value))
+            {
+                messages.push(format!(
+                    "value {} is not one of the enum's values",
+                    attribute.value
+                ));
+            }
+        }
+    }
+    messages
+}
+
+/// Check every attribute's key, type, enum value and deprecation against
+/// the conventions.
+pub fn validate(
+    attributes: &[OtlpAttribute],
+    attribute_map: &HashMap<String, Attribute>,
+) -> Vec<Violation> {
+    attributes
+        .iter()
+        .flat_map(|attribute| {
+            check(attribute, attribute_map)
+                .into_iter()
+                .map(|message| Violation {
+                    line: attribute.line,
+                    context: attribute.context.clone(),
+                    key: attribute.key.clone(),
+                    message,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{otlp, semconv::attribute_map};
+
+    #[test]
+    fn test_validate() {
+        let attribute_map = attribute_map(&[
+            ("http.response.status_code", "id: status_code\ntype: int"),
+            (
+                "http.request.method",
+                "id: method\ntype:\n  members:\n    - value: GET\n    - value: POST",
+            ),
+            ("http.request.header", "id: header\ntype: template[string[]]"),
+            (
+                "http.method",
+                "id: method\ntype: string\ndeprecated:\n  reason: renamed\n  renamed_to: http.request.method",
+            ),
+        ]);
+
+        let spans = r#"{"resourceSpans": [{"scopeSpans": [{"spans": [{
+  "name": "GET /cart",
+  "attributes": [
+    {"key": "http.response.status_code", "value": {"stringValue": "200"}},
+    {"key": "http.request.method", "value": {"stringValue": "FETCH"}},
+    {"key": "http.request.header.accept", "value": {"arrayValue": {"values": [{"stringValue": "*/*"}]}}},
+    {"key": "http.request.header", "value": {"arrayValue": {"values": []}}},
+    {"key": "http.method", "value": {"stringValue": "GET"}},
+    {"key": "cart.size", "value": {"intValue": "3"}}
+  ]
+}]}]}]}"#;
+        let attributes = otlp::read_json(spans).unwrap();
+        let violations: Vec<(usize, String)> = validate(&attributes, &attribute_map)
+            .into_iter()
+            .map(|v| (v.line, format!("{} {}", v.key, v.message)))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                (
+                    4,
+                    "http.response.status_code is string but the convention's type is int"
+                        .to_owned()
+                ),
+                (
+                    5,
+                    "http.request.method value \"FETCH\" is not one of the enum's values".to_owned()
+                ),
+                (
+                    7,
+                    "http.request.header is a template attribute, add a key to it, e.g. http.request.header.<key>"
+                        .to_owned()
+                ),
+                (
+                    8,
+                    "http.method is deprecated: renamed to http.request.method"
+                        .to_owned()
+                ),
+                (9, "cart.size is not defined by any convention".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::{convert::Infallible, path::Path, sync::Arc, time::Duration};
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::Stream;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::{
+    sync::{broadcast::error::RecvError, mpsc},
+    time::Instant,
+};
+
+use crate::{print_problems, semconv::SemanticConventions, source::ModelSource, AppState, Model};
+
+/// How long to wait for a burst of file events, e.g. an editor's save, to settle.
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// The least time between reloads, so services sending new keys one after
+/// another don't keep refreshing the open pages.
+const MIN_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Why the model needs rebuilding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reload {
+    /// A yaml file changed, so the conventions are read again.
+    Files,
+    /// The OTLP receiver added columns, so only the Honeycomb data changed.
+    Columns,
+}
+
+fn is_yaml_change(event: &notify::Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && event.paths.iter().any(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "yaml" || extension == "yml")
+    })
+}
+
+/// Rebuild the model whenever a reload is requested on the returned sender.
+/// The conventions are only read again from `sources` when a yaml file
+/// changed; new columns just apply the current snapshot to `sc`. Bursts of
+/// requests are handled with one reload, at most one every [`MIN_INTERVAL`].
+/// If the new model fails to load the old one is kept.
+pub fn reloader(
+    sources: &[(String, ModelSource)],
+    sc: SemanticConventions,
+    state: AppState,
+) -> mpsc::UnboundedSender<Reload> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sources = sources.to_vec();
+    tokio::spawn(async move {
+        let mut sc = Arc::new(sc);
+        let mut last_reload: Option<Instant> = None;
+        while let Some(reload) = rx.recv().await {
+            tokio::time::sleep(SETTLE).await;
+            if let Some(last_reload) = last_reload {
+                tokio::time::sleep_until(last_reload + MIN_INTERVAL).await;
+            }
+            let mut files = reload == Reload::Files;
+            while let Ok(reload) = rx.try_recv() {
+                files |= reload == Reload::Files;
+            }
+            last_reload = Some(Instant::now());
+
+            let sources = sources.clone();
+            let current = sc.clone();
+            let snapshot = state.snapshot();
+            let result = tokio::task::spawn_blocking(move || {
+                let sc = if files {
+                    Arc::new(SemanticConventions::new(&sources)?)
+                } else {
+                    current
+                };
+                let model = Model::new((*sc).clone(), &snapshot);
+                anyhow::Ok((sc, model))
+            })
+            .await;
+            match result {
+                Ok(Ok((new_sc, model))) => {
+                    println!("reloaded model");
+                    if files {
+                        print_problems(&model.problems);
+                    }
+                    sc = new_sc;
+                    *state.model.write().unwrap() = Arc::new(model);
+                    let _ = state.reloads.send(());
+                }
+                Ok(Err(e)) => eprintln!("Failed to reload model, keeping the previous one: {e:#}"),
+                Err(e) => eprintln!("Failed to reload model: {e}"),
+            }
+        }
+    });
+    tx
+}
+
+/// Watch the model directories, except those read from git, and request a
+/// reload when a yaml file changes. Keep the returned watcher alive for as
+/// long as the directories should be watched.
+pub fn watch(
+    sources: &[(String, ModelSource)],
+    reload: mpsc::UnboundedSender<Reload>,
+) -> anyhow::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|event| is_yaml_change(&event)) {
+            let _ = reload.send(Reload::Files);
+        }
+    })?;
+    for dir in sources.iter().filter_map(|(_, source)| source.watch_dir()) {
+        watcher.watch(Path::new(dir), RecursiveMode::Recursive)?;
+    }
+    Ok(watcher)
+}
+
+/// Server-sent events telling open pages to refresh after a reload.
+pub async fn events_handler(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(state.reloads.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(()) => return Some((Ok(Event::default().event("reload").data("")), rx)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}